@@ -9,7 +9,10 @@ pub use tracing::{
     warn_span,
 };
 
-use std::{io::prelude::*, time::Instant};
+use std::{
+    io::prelude::*,
+    time::{Duration, Instant},
+};
 
 use glium::{
     glutin::{
@@ -21,10 +24,13 @@ use glium::{
     Display,
 };
 
-use la::{Cell, Universe};
+use la::{Budget, Cell, Universe};
 
 use crate::graphics::renderer::Renderer;
 
+// time an evolution may take per tick, before it is continued on the next one
+const EVOLUTION_BUDGET: Duration = Duration::from_millis(8);
+
 pub struct Cremator {
     display: Display,
     event_loop: EventLoop<()>,
@@ -34,6 +40,7 @@ pub struct Cremator {
 
     tick_count: u64,
     _last_tick: Instant,
+    evolution_pending: bool,
 }
 
 impl Cremator {
@@ -59,6 +66,7 @@ impl Cremator {
             universe,
             tick_count: 0,
             _last_tick: Instant::now(),
+            evolution_pending: false,
         }
     }
 
@@ -70,6 +78,7 @@ impl Cremator {
             mut universe,
             mut tick_count,
             mut _last_tick,
+            mut evolution_pending,
         } = self;
         event_loop.run(move |event, _, control_flow| match event {
             // beginning
@@ -78,7 +87,12 @@ impl Cremator {
                 tick_count = tick_count.wrapping_add(1);
             }
             // updating
-            Event::MainEventsCleared => Self::update(&mut universe, &mut renderer, &tick_count),
+            Event::MainEventsCleared => Self::update(
+                &mut universe,
+                &mut renderer,
+                &tick_count,
+                &mut evolution_pending,
+            ),
             // rendering
             Event::RedrawRequested(_) => Self::render(&mut renderer, &universe, &display),
             Event::RedrawEventsCleared => display.gl_window().window().request_redraw(),
@@ -96,9 +110,22 @@ impl Cremator {
         })
     }
 
-    fn update(universe: &mut Universe, renderer: &mut Renderer, tick_count: &u64) {
+    fn update(
+        universe: &mut Universe,
+        renderer: &mut Renderer,
+        tick_count: &u64,
+        evolution_pending: &mut bool,
+    ) {
         if tick_count % 10 == 0 {
-            universe.evolve();
+            *evolution_pending = true;
+        }
+        if *evolution_pending {
+            // an interrupted evolution keeps its memoized results and resumes on the next tick
+            let budget = Budget::unlimited().with_timeout(EVOLUTION_BUDGET);
+            match universe.evolve_with(&budget, |_| {}) {
+                Ok(()) => *evolution_pending = false,
+                Err(_) => trace!("evolution interrupted, continuing on the next tick"),
+            }
         }
         renderer.update();
     }
@@ -126,7 +153,7 @@ impl Cremator {
                     }
                     'o' => {
                         for _ in 0..parameter {
-                            self.universe.set_cell((x, y), Cell::Alive);
+                            self.universe.set_cell((x, y), Cell::ALIVE);
                             x += 1;
                         }
                         argument = 0
@@ -3,7 +3,7 @@ use glium::{
     Display, Frame, Program, Surface,
 };
 
-use la::{Cell, Universe};
+use la::Universe;
 
 use super::camera::{Camera, CAMERA_SPEED, ZOOM_FACTOR};
 
@@ -84,12 +84,7 @@ impl CellRenderer {
 
         for y in y_range {
             for x in x_range.clone() {
-                let alive = match universe.get_cell((x, y)) {
-                    Cell::Dead => false,
-                    Cell::Alive => true,
-                };
-
-                if alive {
+                if !universe.get_cell((x, y)).is_dead() {
                     let mut new = self.camera.project((x, y));
                     vertices.append(&mut new);
                 }
@@ -0,0 +1,159 @@
+use std::{
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    table::{Limit, Table, PROGRESS_DEPTH},
+    universe::Universe,
+};
+
+// the clock is only read every this many computed results, must be a power of two
+const CLOCK_INTERVAL: u64 = 256;
+
+/// Limits the time and work a single [`Universe::evolve_with`] may spend.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    deadline: Option<Instant>,
+    max_work: Option<u64>,
+    cancel: Option<CancelToken>,
+}
+
+/// Cancels an evolution from another thread.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+/// The evolution was stopped before the next generation was reached.
+///
+/// All results computed until then stay memoized, so retrying continues where it stopped.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Interrupted;
+
+impl Budget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Stops at `deadline`.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Stops after `timeout` has passed, counted from now.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Stops after `max_work` node results had to be computed.
+    pub fn with_max_work(mut self, max_work: u64) -> Self {
+        self.max_work = Some(max_work);
+        self
+    }
+
+    /// Stops as soon as `token` is cancelled.
+    pub fn with_cancel(mut self, token: &CancelToken) -> Self {
+        self.cancel = Some(token.clone());
+        self
+    }
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Makes the token usable for another evolution.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+impl fmt::Display for Interrupted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "evolution was interrupted")
+    }
+}
+
+impl Error for Interrupted {}
+
+struct Limiter<'a, F> {
+    budget: &'a Budget,
+    work: u64,
+    interrupted: bool,
+    // counted in results at `PROGRESS_DEPTH`
+    done: u32,
+    progress: F,
+}
+
+impl<F: FnMut(f64)> Limit for Limiter<'_, F> {
+    fn proceed(&mut self) -> bool {
+        self.work += 1;
+
+        let budget = self.budget;
+        let exhausted = matches!(budget.max_work, Some(max) if self.work > max);
+        let cancelled = matches!(&budget.cancel, Some(token) if token.is_cancelled());
+        let overdue = self.work & (CLOCK_INTERVAL - 1) == 0
+            && matches!(budget.deadline, Some(deadline) if Instant::now() >= deadline);
+        self.interrupted |= exhausted || cancelled || overdue;
+
+        !self.interrupted
+    }
+
+    fn finished(&mut self, depth: u32) {
+        if depth <= PROGRESS_DEPTH {
+            self.done += 4u32.pow(PROGRESS_DEPTH - depth);
+            (self.progress)(f64::from(self.done) / f64::from(4u32.pow(PROGRESS_DEPTH)));
+        }
+    }
+}
+
+impl Universe {
+    /// Evolves the universe by one generation like [`Universe::evolve`],
+    /// unless the `budget` runs out first.
+    ///
+    /// `progress` is called with the fraction of the work done so far, measured in finished
+    /// sub-results two levels below the root. If the evolution is interrupted, the universe
+    /// stays at the current generation.
+    pub fn evolve_with(
+        &mut self,
+        budget: &Budget,
+        progress: impl FnMut(f64),
+    ) -> Result<(), Interrupted> {
+        let mut store = self.store.lock_for_evolution(self.generation);
+        let root = self.join_edges(&mut *store);
+        let root = store.expand_for_evolution(root);
+        // keeps the expansion, but not the border a bounded grid copied its edges into
+        if self.grid.is_none() {
+            self.root = Some(root);
+        }
+
+        let mut limiter = Limiter {
+            budget,
+            work: 0,
+            interrupted: false,
+            done: 0,
+            progress,
+        };
+        let result = store
+            .try_evolve_tree(root, 0, &mut limiter)
+            .ok_or(Interrupted)?;
+
+        self.root = Some(self.clip(&mut *store, result));
+        self.generation += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,534 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Write},
+    ops::RangeInclusive,
+};
+
+use crate::{
+    core::{Cell, Offset, Position},
+    escape::{lane_and_timing, Escape, EscapeReport, Spaceship},
+    pattern::{Pattern, Transform},
+    rule::Rule,
+    universe::Universe,
+};
+
+// the name, cells and period of an object
+type Object = (&'static str, &'static [(i64, i64)], usize);
+
+// the common still lifes and oscillators of Life
+const LIFE_OBJECTS: [Object; 12] = [
+    ("block", &[(0, 0), (1, 0), (0, 1), (1, 1)], 1),
+    (
+        "beehive",
+        &[(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)],
+        1,
+    ),
+    (
+        "loaf",
+        &[(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (3, 2), (2, 3)],
+        1,
+    ),
+    ("boat", &[(0, 0), (1, 0), (0, 1), (2, 1), (1, 2)], 1),
+    ("ship", &[(0, 0), (1, 0), (0, 1), (2, 1), (1, 2), (2, 2)], 1),
+    ("tub", &[(1, 0), (0, 1), (2, 1), (1, 2)], 1),
+    (
+        "pond",
+        &[
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (3, 1),
+            (0, 2),
+            (3, 2),
+            (1, 3),
+            (2, 3),
+        ],
+        1,
+    ),
+    (
+        "long boat",
+        &[(0, 0), (1, 0), (0, 1), (2, 1), (1, 2), (3, 2), (2, 3)],
+        1,
+    ),
+    (
+        "barge",
+        &[(1, 0), (0, 1), (2, 1), (1, 2), (3, 2), (2, 3)],
+        1,
+    ),
+    ("blinker", &[(0, 0), (1, 0), (2, 0)], 2),
+    ("toad", &[(1, 0), (2, 0), (3, 0), (0, 1), (1, 1), (2, 1)], 2),
+    (
+        "beacon",
+        &[(0, 0), (1, 0), (0, 1), (3, 2), (2, 3), (3, 3)],
+        2,
+    ),
+];
+
+/// Runs every collision of spaceships with a target, over a range of lanes and timings.
+///
+/// The target is placed with its origin at the origin, and each incoming spaceship heads
+/// towards it from far enough away. Each collision runs until what remains repeats itself,
+/// while spaceships leaving the area are removed and reported, see [`Universe::escapes`].
+#[derive(Debug, Clone)]
+pub struct CollisionSearch {
+    rule: Rule,
+    target: Pattern,
+    incoming: Vec<(Spaceship, Transform)>,
+    shifts: RangeInclusive<i64>,
+    delays: RangeInclusive<i64>,
+    max_generations: usize,
+    ships: Vec<Spaceship>,
+    // the canonical digest of every phase of known objects
+    objects: Vec<(u128, &'static str)>,
+}
+
+/// Where an incoming spaceship of a [`Collision`] started.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Placement {
+    /// How many cells the spaceship was moved sideways from the path through the origin.
+    pub shift: i64,
+    /// How many generations the spaceship arrives later.
+    pub delay: i64,
+    /// The lane of the spaceship, as in [`Escape::lane`].
+    pub lane: i64,
+    /// The timing of the spaceship, as in [`Escape::timing`].
+    pub timing: i64,
+}
+
+/// An object remaining after a [`Collision`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Product {
+    /// The name of the object, if it is a common one.
+    pub name: Option<&'static str>,
+    /// The [`Pattern::canonical_digest`] of the object in its final phase.
+    pub digest: u128,
+    /// The north west corner of its bounding box.
+    pub position: Position,
+    pub population: u32,
+}
+
+/// How a [`Collision`] ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// The generation from which on the remains repeat, or `None` if they didn't before
+    /// the search gave up.
+    pub settled: Option<usize>,
+    /// The period of the remains, or 0 if they didn't settle.
+    pub period: usize,
+    /// The objects remaining, where any objects closer than three cells count as one.
+    pub products: Vec<Product>,
+    /// The spaceships leaving the area, which includes incoming ones missing the target.
+    pub emitted: Vec<Escape>,
+}
+
+/// A single collision run by [`CollisionSearch::run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    /// A placement for each incoming spaceship, in the order they were added.
+    pub incoming: Vec<Placement>,
+    pub outcome: Outcome,
+}
+
+/// All collisions of a [`CollisionSearch`], in the order of their placements.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CollisionTable {
+    pub collisions: Vec<Collision>,
+}
+
+impl Outcome {
+    /// Whether nothing is left, not even a spaceship.
+    pub fn is_annihilation(&self) -> bool {
+        self.settled.is_some() && self.products.is_empty() && self.emitted.is_empty()
+    }
+
+    /// How many of the products are called `name`.
+    pub fn count(&self, name: &str) -> usize {
+        self.products
+            .iter()
+            .filter(|product| product.name == Some(name))
+            .count()
+    }
+}
+
+impl CollisionTable {
+    /// The collisions with at least one product called `name`.
+    pub fn producing<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Collision> + 'a {
+        self.collisions
+            .iter()
+            .filter(move |collision| collision.outcome.count(name) > 0)
+    }
+
+    pub fn annihilations(&self) -> impl Iterator<Item = &Collision> {
+        self.collisions
+            .iter()
+            .filter(|collision| collision.outcome.is_annihilation())
+    }
+
+    /// The table as comma separated values with a header line. Lists within a column are
+    /// separated by semicolons, and unknown products are named by their digest in hex.
+    ///
+    /// List items containing semicolons or quotes, and columns containing commas or quotes,
+    /// are quoted with their quotes doubled, as for spaceships with such names.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("lanes,timings,settled,period,products,emitted\n");
+        for collision in &self.collisions {
+            let join = |items: Vec<String>| {
+                let items: Vec<String> = items.iter().map(|item| quoted(item, ';')).collect();
+                quoted(&items.join(";"), ',')
+            };
+            let incoming = &collision.incoming;
+            let outcome = &collision.outcome;
+            let products = outcome.products.iter().map(|product| {
+                let name = product
+                    .name
+                    .map_or_else(|| format!("{:032x}", product.digest), str::to_string);
+                format!("{}@{}:{}", name, product.position.x, product.position.y)
+            });
+            let emitted = outcome.emitted.iter().map(|escape| {
+                format!(
+                    "{}({} {})@{}:{}",
+                    escape.ship, escape.velocity.dx, escape.velocity.dy, escape.lane, escape.timing
+                )
+            });
+            writeln!(
+                csv,
+                "{},{},{},{},{},{}",
+                join(incoming.iter().map(|p| p.lane.to_string()).collect()),
+                join(incoming.iter().map(|p| p.timing.to_string()).collect()),
+                outcome.settled.map_or(String::new(), |g| g.to_string()),
+                outcome.period,
+                join(products.collect()),
+                join(emitted.collect()),
+            )
+            .unwrap();
+        }
+        csv
+    }
+}
+
+/// Error of a [`CollisionSearch`] whose incoming spaceship doesn't move like one under the
+/// search's rule, so that its lane and timing are unknown.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotASpaceship {
+    name: String,
+}
+
+impl NotASpaceship {
+    /// Name of the incoming spaceship.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for NotASpaceship {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the incoming {} isn't a spaceship", self.name)
+    }
+}
+
+impl Error for NotASpaceship {}
+
+// `field` in quotes if it contains `separator`, quotes or line breaks
+fn quoted(field: &str, separator: char) -> String {
+    if field.contains([separator, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl CollisionSearch {
+    /// Collides spaceships with `target`, which may be empty or move itself, under `rule`.
+    ///
+    /// By default spaceships are shifted by up to 8 cells to either side and delayed by up
+    /// to 3 generations, and collisions run for at most 1000 generations. With Life, the
+    /// spaceships of [`Spaceship::life`] are recognized when they leave.
+    pub fn new(rule: Rule, target: Pattern) -> Self {
+        let ships = if rule == Rule::life() {
+            Spaceship::life()
+        } else {
+            Vec::new()
+        };
+        let objects = if rule == Rule::life() {
+            life_objects()
+        } else {
+            Vec::new()
+        };
+        Self {
+            rule,
+            target,
+            incoming: Vec::new(),
+            shifts: -8..=8,
+            delays: 0..=3,
+            max_generations: 1000,
+            ships,
+            objects,
+        }
+    }
+
+    /// Adds an incoming spaceship heading in the direction `transform` turns it to.
+    pub fn with_incoming(mut self, ship: &Spaceship, transform: Transform) -> Self {
+        self.incoming.push((ship.clone(), transform));
+        self
+    }
+
+    /// Shifts the incoming spaceships sideways by each of `shifts` cells.
+    pub fn with_shifts(mut self, shifts: RangeInclusive<i64>) -> Self {
+        self.shifts = shifts;
+        self
+    }
+
+    /// Delays the incoming spaceships by each of `delays` generations.
+    pub fn with_delays(mut self, delays: RangeInclusive<i64>) -> Self {
+        self.delays = delays;
+        self
+    }
+
+    /// Gives up on collisions which didn't settle after `max_generations` generations.
+    pub fn with_max_generations(mut self, max_generations: usize) -> Self {
+        self.max_generations = max_generations;
+        self
+    }
+
+    /// Recognizes `ships` when they leave the area.
+    pub fn with_ships(mut self, ships: Vec<Spaceship>) -> Self {
+        self.ships = ships;
+        self
+    }
+
+    /// Runs the collisions for every combination of shifts and delays of the incoming
+    /// spaceships, skipping those where they start next to each other.
+    ///
+    /// Fails if an incoming spaceship doesn't move like one under the search's rule, since
+    /// its lane and timing would be unknown.
+    pub fn run(&self) -> Result<CollisionTable, NotASpaceship> {
+        if let Some((ship, _)) = self
+            .incoming
+            .iter()
+            .find(|(ship, _)| !ship.moves_under(&self.rule))
+        {
+            return Err(NotASpaceship {
+                name: ship.name().to_string(),
+            });
+        }
+        let mut table = CollisionTable::default();
+        let choices: Vec<(i64, i64)> = self
+            .shifts
+            .clone()
+            .flat_map(|shift| self.delays.clone().map(move |delay| (shift, delay)))
+            .collect();
+        if self.incoming.is_empty() || choices.is_empty() {
+            return Ok(table);
+        }
+        // each combination counts up in base `choices.len()`
+        let mut combination = vec![0; self.incoming.len()];
+        loop {
+            let placements: Vec<(i64, i64)> = combination.iter().map(|&i| choices[i]).collect();
+            if let Some(collision) = self.collide(&placements) {
+                table.collisions.push(collision);
+            }
+            let next = combination.iter().rposition(|&i| i + 1 < choices.len());
+            match next {
+                Some(digit) => {
+                    combination[digit] += 1;
+                    for i in &mut combination[digit + 1..] {
+                        *i = 0;
+                    }
+                }
+                None => break,
+            }
+        }
+        Ok(table)
+    }
+
+    // the number of periods spaceships start back from the path through the origin,
+    // enough to start clear of the target and each other
+    fn lead(&self, period: i64) -> i64 {
+        let extent = self
+            .target
+            .cells()
+            .iter()
+            .map(|&(offset, _)| offset.dx.abs().max(offset.dy.abs()))
+            .max()
+            .unwrap_or(0);
+        let shift = self.shifts.start().abs().max(self.shifts.end().abs());
+        let early = (-self.delays.start()).max(0);
+        extent + shift + 16 + (early + period - 1) / period
+    }
+
+    // runs the collision with the incoming spaceships shifted and delayed by `placements`,
+    // unless they start next to each other
+    fn collide(&self, placements: &[(i64, i64)]) -> Option<Collision> {
+        let mut universe = Universe::with_rule(self.rule.clone());
+        universe.initialize();
+        universe.paste(&self.target, Position::ORIGIN);
+        let mut cells: Vec<Position> = self
+            .target
+            .cells()
+            .iter()
+            .map(|&(offset, _)| Position::ORIGIN + offset)
+            .collect();
+
+        let mut incoming = Vec::new();
+        for ((ship, transform), &(shift, delay)) in self.incoming.iter().zip(placements) {
+            let transform = *transform;
+            let period = ship.period() as i64;
+            let velocity = transform.apply(ship.displacement());
+            let step = if velocity.dy != 0 {
+                Offset::new(1, 0)
+            } else {
+                Offset::new(0, 1)
+            };
+            // the spaceship starts `back` generations before it would pass the origin
+            let back = self.lead(period) * period + delay;
+            let periods = (back + period - 1) / period;
+            let phase = (periods * period - back) as usize;
+            let origin = Position::new(
+                shift * step.dx - periods * velocity.dx,
+                shift * step.dy - periods * velocity.dy,
+            );
+            let image = ship.phases[phase].transformed(transform);
+            let body: Vec<Position> = image
+                .cells()
+                .iter()
+                .map(|&(offset, _)| origin + offset)
+                .collect();
+            let near = body.iter().any(|a| {
+                cells
+                    .iter()
+                    .any(|b| (a.x - b.x).abs() <= 2 && (a.y - b.y).abs() <= 2)
+            });
+            if near {
+                return None;
+            }
+
+            // lanes and timings as the spaceship would be reported leaving. `run` checked that
+            // it moves under the rule, so it is always recognized on its own.
+            let mut alone = Universe::with_rule(self.rule.clone());
+            alone.initialize();
+            alone.paste(&image, origin);
+            let &(phase, pos, transform) = alone.spaceships(ship).first()?;
+            let velocity = transform.apply(ship.displacement());
+            let (lane, timing) = lane_and_timing(pos, phase, 0, velocity, ship.period());
+            incoming.push(Placement {
+                shift,
+                delay,
+                lane,
+                timing,
+            });
+
+            universe.paste(&image, origin);
+            cells.extend(body);
+        }
+
+        Some(Collision {
+            incoming,
+            outcome: self.outcome(universe, &cells),
+        })
+    }
+
+    // runs `universe` until it settles, watching for spaceships leaving around `cells`
+    fn outcome(&self, mut universe: Universe, cells: &[Position]) -> Outcome {
+        let (mut min, mut max) = (cells[0], cells[0]);
+        for cell in cells {
+            min = Position::new(min.x.min(cell.x), min.y.min(cell.y));
+            max = Position::new(max.x.max(cell.x), max.y.max(cell.y));
+        }
+        let (min, max) = (min - Offset::new(4, 4), max + Offset::new(4, 4));
+
+        let mut report = EscapeReport::default();
+        // the generation each arrangement of the remaining cells was first seen in
+        let mut seen: HashMap<(u128, i64, i64), usize> = HashMap::new();
+        let mut settled = None;
+        for generation in 0..=self.max_generations {
+            universe.remove_escapes(&self.ships, min, max, &mut report);
+            let pattern = universe.to_pattern();
+            let corner = if pattern.is_empty() {
+                Offset::default()
+            } else {
+                pattern.bounds().0
+            };
+            let key = (pattern.digest(), corner.dx, corner.dy);
+            if let Some(first) = seen.insert(key, generation) {
+                settled = Some((first, generation - first));
+                break;
+            }
+            universe.evolve();
+        }
+
+        Outcome {
+            settled: settled.map(|(first, _)| first),
+            period: settled.map_or(0, |(_, period)| period),
+            products: self.products(&universe),
+            emitted: report.escapes,
+        }
+    }
+
+    // the objects in `universe`, made of the cells closer than three cells to each other
+    fn products(&self, universe: &Universe) -> Vec<Product> {
+        let cells: Vec<(Position, Cell)> = universe
+            .to_pattern()
+            .cells()
+            .iter()
+            .map(|&(offset, cell)| (Position::ORIGIN + offset, cell))
+            .collect();
+        // the cell with the smallest index of each cell's object, as a union-find forest
+        let mut parent: Vec<usize> = (0..cells.len()).collect();
+        fn root(parent: &[usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                i = parent[i];
+            }
+            i
+        }
+        for i in 0..cells.len() {
+            for j in i + 1..cells.len() {
+                let (a, b) = (cells[i].0, cells[j].0);
+                if (a.x - b.x).abs() <= 2 && (a.y - b.y).abs() <= 2 {
+                    let (ri, rj) = (root(&parent, i), root(&parent, j));
+                    parent[ri.max(rj)] = ri.min(rj);
+                }
+            }
+        }
+
+        let mut products = Vec::new();
+        for i in 0..cells.len() {
+            if root(&parent, i) != i {
+                continue;
+            }
+            let object = Pattern::with_cells(
+                (0..cells.len())
+                    .filter(|&j| root(&parent, j) == i)
+                    .map(|j| (Offset::new(cells[j].0.x, cells[j].0.y), cells[j].1)),
+            );
+            let digest = object.canonical_digest();
+            let (corner, _) = object.bounds();
+            products.push(Product {
+                name: self
+                    .objects
+                    .iter()
+                    .find(|&&(known, _)| known == digest)
+                    .map(|&(_, name)| name),
+                digest,
+                position: Position::ORIGIN + corner,
+                population: object.cells().len() as u32,
+            });
+        }
+        products.sort_by_key(|product| product.position);
+        products
+    }
+}
+
+fn life_objects() -> Vec<(u128, &'static str)> {
+    let mut objects = Vec::new();
+    for &(name, cells, period) in &LIFE_OBJECTS {
+        let mut universe = Universe::with_rule(Rule::life());
+        universe.initialize();
+        universe.paste(&Pattern::new(cells), Position::ORIGIN);
+        for _ in 0..period {
+            objects.push((universe.to_pattern().canonical_digest(), name));
+            universe.evolve();
+        }
+    }
+    objects
+}
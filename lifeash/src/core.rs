@@ -4,14 +4,12 @@ use std::{
     ops::{Add, AddAssign, Sub, SubAssign},
 };
 
-#[repr(u8)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum Cell {
-    Dead = 0u8,
-    Alive = 1u8,
-}
+/// State of a single cell. Two-state rules only use `DEAD` and `ALIVE`,
+/// multi-state rules number their remaining states from 2 upwards.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cell(u8);
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Position {
     pub x: i64,
     pub y: i64,
@@ -27,7 +25,7 @@ pub(crate) enum Quadrant {
 }
 
 // use enum instead with East, West, etc. variants?
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Offset {
     pub dx: i64,
     pub dy: i64,
@@ -36,6 +34,23 @@ pub struct Offset {
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub(crate) struct Level(u8);
 
+impl Cell {
+    pub const DEAD: Self = Self(0);
+    pub const ALIVE: Self = Self(1);
+
+    pub const fn new(state: u8) -> Self {
+        Self(state)
+    }
+
+    pub const fn state(self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_dead(self) -> bool {
+        self.0 == 0
+    }
+}
+
 impl From<(i64, i64)> for Position {
     fn from(t: (i64, i64)) -> Self {
         Self::new(t.0, t.1)
@@ -43,7 +58,6 @@ impl From<(i64, i64)> for Position {
 }
 
 impl Position {
-    #[allow(dead_code)]
     pub const ORIGIN: Self = Self::new(0, 0);
 
     pub const fn new(x: i64, y: i64) -> Self {
@@ -78,7 +92,7 @@ impl From<(i64, i64)> for Offset {
 impl Add for Offset {
     type Output = Self;
     fn add(self, other: Self) -> Self::Output {
-        Offset::new(self.dx + other.dy, self.dy + other.dy)
+        Offset::new(self.dx + other.dx, self.dy + other.dy)
     }
 }
 
@@ -92,7 +106,7 @@ impl AddAssign for Offset {
 impl Sub for Offset {
     type Output = Self;
     fn sub(self, other: Self) -> Self::Output {
-        Offset::new(self.dx - other.dy, self.dy - other.dy)
+        Offset::new(self.dx - other.dx, self.dy - other.dy)
     }
 }
 
@@ -221,7 +235,7 @@ impl Level {
     pub(crate) const MAX_LEVEL: Self = Self(63);
     pub(crate) const LEAF_LEVEL: Self = Self(0);
 
-    pub(crate) fn new(n: u8) -> Self {
+    pub(crate) const fn new(n: u8) -> Self {
         Self(n)
     }
 
@@ -247,17 +261,15 @@ impl Level {
         (1 << (self.0 - 1)) - 1
     }
 
-    pub(crate) const fn coord_range(self) -> std::ops::Range<i64> {
-        self.min_coord()..self.max_coord()
+    pub(crate) const fn coord_range(self) -> std::ops::RangeInclusive<i64> {
+        self.min_coord()..=self.max_coord()
     }
 
-    #[allow(dead_code)]
     pub(crate) fn min_pos(self) -> Position {
         let min = Self::min_coord(self);
         (min, min).into()
     }
 
-    #[allow(dead_code)]
     pub(crate) fn max_pos(self) -> Position {
         let max = Self::max_coord(self);
         (max, max).into()
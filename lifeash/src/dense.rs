@@ -0,0 +1,162 @@
+use std::convert::TryFrom;
+
+use crate::{
+    core::{Cell, Offset, Position},
+    engine::Engine,
+    rule::Rule,
+};
+
+/// Brute-force engine on a dense grid, which covers exactly the bounding box of the pattern.
+///
+/// Every generation looks at every cell of the box, so it is slow,
+/// but simple enough to serve as the reference the other engines are tested against.
+/// Rules with `B0` are run directly, with all cells outside of the box in the background state.
+#[derive(Debug, Clone, Default)]
+pub struct DenseGrid {
+    // position of the north west corner of the box
+    origin: Position,
+    width: usize,
+    height: usize,
+    cells: Vec<Cell>,
+    generation: usize,
+    rule: Rule,
+    // state of all cells outside of the box
+    background: Cell,
+}
+
+impl DenseGrid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rule(rule: Rule) -> Self {
+        Self {
+            rule,
+            ..Self::default()
+        }
+    }
+
+    fn index(&self, pos: Position) -> Option<usize> {
+        let x = usize::try_from(pos.x - self.origin.x).ok()?;
+        let y = usize::try_from(pos.y - self.origin.y).ok()?;
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    fn positions(&self) -> impl Iterator<Item = Position> {
+        let (origin, width, height) = (self.origin, self.width as i64, self.height as i64);
+        (0..height).flat_map(move |y| (0..width).map(move |x| origin + Offset::new(x, y)))
+    }
+
+    // resizes the box to the given corners, keeping all cells inside of it
+    fn reframe(&mut self, min: Position, max: Position) {
+        let (origin, width, height) = if min.x <= max.x && min.y <= max.y {
+            let (width, height) = (max.x - min.x + 1, max.y - min.y + 1);
+            (min, width as usize, height as usize)
+        } else {
+            (Position::ORIGIN, 0, 0)
+        };
+        let mut grid = Self {
+            origin,
+            width,
+            height,
+            cells: vec![self.background; width * height],
+            generation: self.generation,
+            rule: self.rule.clone(),
+            background: self.background,
+        };
+        for pos in grid.positions().collect::<Vec<_>>() {
+            let i = grid.index(pos).unwrap();
+            grid.cells[i] = Engine::get_cell(self, pos);
+        }
+        *self = grid;
+    }
+
+    // shrinks the box to the bounding box of the cells which differ from the background
+    fn trim(&mut self) {
+        let alive = self
+            .positions()
+            .filter(|&pos| Engine::get_cell(self, pos) != self.background);
+        let (min, max) = alive.fold(
+            (
+                Position::new(i64::MAX, i64::MAX),
+                Position::new(i64::MIN, i64::MIN),
+            ),
+            |(min, max), pos| {
+                (
+                    Position::new(min.x.min(pos.x), min.y.min(pos.y)),
+                    Position::new(max.x.max(pos.x), max.y.max(pos.y)),
+                )
+            },
+        );
+        self.reframe(min, max);
+    }
+}
+
+impl Engine for DenseGrid {
+    fn set_cell(&mut self, pos: Position, cell: Cell) {
+        if self.index(pos).is_none() {
+            if cell == self.background {
+                return;
+            }
+            let (min, max) = if self.cells.is_empty() {
+                (pos, pos)
+            } else {
+                let max = self.origin + Offset::new(self.width as i64 - 1, self.height as i64 - 1);
+                (
+                    Position::new(self.origin.x.min(pos.x), self.origin.y.min(pos.y)),
+                    Position::new(max.x.max(pos.x), max.y.max(pos.y)),
+                )
+            };
+            self.reframe(min, max);
+        }
+        let i = self.index(pos).unwrap();
+        self.cells[i] = cell;
+    }
+
+    fn get_cell(&self, pos: Position) -> Cell {
+        self.index(pos).map_or(self.background, |i| self.cells[i])
+    }
+
+    fn evolve(&mut self) {
+        let rule = self.rule.in_generation(self.generation);
+        let background = rule.next_at(Position::ORIGIN, self.background, |_| self.background);
+        if self.cells.is_empty() {
+            self.background = background;
+            self.generation += 1;
+            return;
+        }
+
+        // births can only happen within the radius of the rule around the box
+        let radius = i64::from(self.rule.radius());
+        let margin = Offset::new(radius, radius);
+        let max = self.origin + Offset::new(self.width as i64, self.height as i64) + margin
+            - Offset::new(1, 1);
+        let mut next = self.clone();
+        next.reframe(self.origin - margin, max);
+        for pos in next.positions().collect::<Vec<_>>() {
+            let cell = Engine::get_cell(self, pos);
+            let i = next.index(pos).unwrap();
+            next.cells[i] = rule.next_at(pos, cell, |offset| Engine::get_cell(self, pos + offset));
+        }
+
+        next.background = background;
+        next.trim();
+        next.generation += 1;
+        *self = next;
+    }
+
+    fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn population(&self) -> u32 {
+        self.cells
+            .iter()
+            .filter(|&&cell| cell != self.background)
+            .count() as u32
+    }
+}
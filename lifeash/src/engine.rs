@@ -0,0 +1,42 @@
+use crate::{
+    core::{Cell, Position},
+    universe::Universe,
+};
+
+/// Common interface of the simulation engines,
+/// so patterns can be run on whichever engine suits them best.
+pub trait Engine {
+    fn set_cell(&mut self, pos: Position, cell: Cell);
+
+    fn get_cell(&self, pos: Position) -> Cell;
+
+    /// Advances by one generation.
+    fn evolve(&mut self);
+
+    fn generation(&self) -> usize;
+
+    /// Number of alive cells.
+    fn population(&self) -> u32;
+}
+
+impl Engine for Universe {
+    fn set_cell(&mut self, pos: Position, cell: Cell) {
+        Universe::set_cell(self, pos, cell)
+    }
+
+    fn get_cell(&self, pos: Position) -> Cell {
+        Universe::get_cell(self, pos)
+    }
+
+    fn evolve(&mut self) {
+        Universe::evolve(self)
+    }
+
+    fn generation(&self) -> usize {
+        Universe::generation(self)
+    }
+
+    fn population(&self) -> u32 {
+        Universe::population(self)
+    }
+}
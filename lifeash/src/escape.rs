@@ -0,0 +1,377 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{
+    core::{Cell, Offset, Position},
+    pattern::{find_around, Pattern, Transform},
+    rule::Rule,
+    universe::Universe,
+};
+
+/// A spaceship to recognize in [`Universe::escapes`], given by its phases under a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Spaceship {
+    name: String,
+    // the pattern in each generation of a period, all relative to the same origin
+    pub(crate) phases: Vec<Pattern>,
+    displacement: Offset,
+}
+
+impl Spaceship {
+    /// Creates the spaceship with the alive `cells` in its first phase, which move by
+    /// `displacement` every `period` generations under `rule`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cells don't move like that.
+    pub fn new(
+        name: &str,
+        rule: &Rule,
+        cells: &[(i64, i64)],
+        period: usize,
+        displacement: Offset,
+    ) -> Self {
+        assert!(period > 0, "a spaceship has a period");
+        assert!(displacement != Offset::new(0, 0), "a spaceship moves");
+        let mut universe = Universe::with_rule(rule.clone());
+        universe.initialize();
+        for &pos in cells {
+            universe.set_cell(pos, Cell::ALIVE);
+        }
+        let mut phases = Vec::new();
+        for _ in 0..period {
+            phases.push(Pattern::with_cells(
+                universe
+                    .alive_cells()
+                    .into_iter()
+                    .map(|pos| (Offset::new(pos.x, pos.y), universe.get_cell(pos))),
+            ));
+            universe.evolve();
+        }
+        let moved = Pattern::new(
+            &cells
+                .iter()
+                .map(|&(x, y)| (x + displacement.dx, y + displacement.dy))
+                .collect::<Vec<_>>(),
+        );
+        assert!(
+            universe.alive_cells().len() == moved.cells().len()
+                && moved
+                    .cells()
+                    .iter()
+                    .all(|&(offset, _)| universe.get_cell((offset.dx, offset.dy)) == Cell::ALIVE),
+            "the cells don't move by the displacement in a period"
+        );
+        Self {
+            name: name.to_string(),
+            phases,
+            displacement,
+        }
+    }
+
+    /// The glider and the light, middle and heavy weight spaceships of Life.
+    pub fn life() -> Vec<Self> {
+        let life = Rule::life();
+        let west = Offset::new(-2, 0);
+        vec![
+            Self::new(
+                "glider",
+                &life,
+                &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+                4,
+                Offset::new(1, 1),
+            ),
+            Self::new(
+                "LWSS",
+                &life,
+                &[
+                    (1, 0),
+                    (4, 0),
+                    (0, 1),
+                    (0, 2),
+                    (4, 2),
+                    (0, 3),
+                    (1, 3),
+                    (2, 3),
+                    (3, 3),
+                ],
+                4,
+                west,
+            ),
+            Self::new(
+                "MWSS",
+                &life,
+                &[
+                    (3, 0),
+                    (1, 1),
+                    (5, 1),
+                    (0, 2),
+                    (0, 3),
+                    (5, 3),
+                    (0, 4),
+                    (1, 4),
+                    (2, 4),
+                    (3, 4),
+                    (4, 4),
+                ],
+                4,
+                west,
+            ),
+            Self::new(
+                "HWSS",
+                &life,
+                &[
+                    (3, 0),
+                    (4, 0),
+                    (1, 1),
+                    (6, 1),
+                    (0, 2),
+                    (0, 3),
+                    (6, 3),
+                    (0, 4),
+                    (1, 4),
+                    (2, 4),
+                    (3, 4),
+                    (4, 4),
+                    (5, 4),
+                ],
+                4,
+                west,
+            ),
+        ]
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn period(&self) -> usize {
+        self.phases.len()
+    }
+
+    /// How far the first phase moves in a period.
+    pub fn displacement(&self) -> Offset {
+        self.displacement
+    }
+
+    // whether the first phase moves by the displacement in a period under `rule`, which the
+    // spaceship may not have been made for
+    pub(crate) fn moves_under(&self, rule: &Rule) -> bool {
+        let mut universe = Universe::with_rule(rule.clone());
+        universe.initialize();
+        universe.paste(&self.phases[0], Position::ORIGIN);
+        for _ in 0..self.period() {
+            universe.evolve();
+        }
+        let cells = self.phases[0].cells();
+        universe.population() as usize == cells.len()
+            && cells.iter().all(|&(offset, cell)| {
+                universe.get_cell(Position::ORIGIN + offset + self.displacement) == cell
+            })
+    }
+}
+
+/// A spaceship leaving the region watched by [`Universe::escapes`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Escape {
+    pub ship: String,
+    /// The generation in which the spaceship was first seen outside of the region.
+    pub generation: usize,
+    /// Where the origin of the first phase of the spaceship is, as reported by
+    /// [`Universe::find`].
+    pub position: Position,
+    pub transform: Transform,
+    /// The phase the spaceship was in, counted in generations from its first phase.
+    pub phase: usize,
+    /// How far the spaceship moves in a period, in the direction it is heading.
+    pub velocity: Offset,
+    pub period: usize,
+    /// The line the spaceship moves on, the same for all spaceships following each other.
+    pub lane: i64,
+    /// The generation, rounded down, in which the spaceship was or will be in its first
+    /// phase on the line through the origin perpendicular to its lane. Spaceships following
+    /// each other on a lane differ in timing by the generations between them.
+    pub timing: i64,
+}
+
+/// The spaceships leaving a region in the order they left, see [`Universe::escapes`].
+///
+/// Its display has a line per spaceship, so reports of different runs can be diffed.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EscapeReport {
+    pub escapes: Vec<Escape>,
+}
+
+impl fmt::Display for EscapeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for escape in &self.escapes {
+            writeln!(
+                f,
+                "generation {}: {} moving ({}, {})/{} on lane {} with timing {}",
+                escape.generation,
+                escape.ship,
+                escape.velocity.dx,
+                escape.velocity.dy,
+                escape.period,
+                escape.lane,
+                escape.timing
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// the lane and timing of a spaceship in the given `phase` at `position` in `generation`,
+// see `Escape`
+pub(crate) fn lane_and_timing(
+    position: Position,
+    phase: usize,
+    generation: usize,
+    velocity: Offset,
+    period: usize,
+) -> (i64, i64) {
+    let (dx, dy) = (velocity.dx, velocity.dy);
+    let first = generation as i64 - phase as i64;
+    let along = position.x * dx + position.y * dy;
+    let lane = (dy * position.x - dx * position.y) / gcd(dx, dy);
+    let timing =
+        (first * (dx * dx + dy * dy) - period as i64 * along).div_euclid(dx * dx + dy * dy);
+    (lane, timing)
+}
+
+// the copies of `ship` among `cells` which contain one of the `alive` cells, sorted like
+// those of `Universe::spaceships`
+fn spaceships_around(
+    cells: &HashMap<(i64, i64), Cell>,
+    ship: &Spaceship,
+    alive: &[Position],
+) -> Vec<(usize, Position, Transform)> {
+    sorted_by_orientation(ship, |pattern| {
+        find_around(cells, pattern, &Transform::ALL, alive)
+    })
+}
+
+// the matches `find` reports for each phase of `ship`, sorted by the orientation's index in
+// `Transform::ALL` and the phase
+fn sorted_by_orientation(
+    ship: &Spaceship,
+    mut find: impl FnMut(&Pattern) -> Vec<(Position, Transform)>,
+) -> Vec<(usize, Position, Transform)> {
+    let mut matches = Vec::new();
+    for (phase, pattern) in ship.phases.iter().enumerate() {
+        for (pos, transform) in find(pattern) {
+            let index = Transform::ALL.iter().position(|&t| t == transform);
+            matches.push((index, phase, pos, transform));
+        }
+    }
+    matches.sort_by_key(|&(index, phase, _, _)| (index, phase));
+    matches
+        .into_iter()
+        .map(|(_, phase, pos, transform)| (phase, pos, transform))
+        .collect()
+}
+
+impl Universe {
+    /// Evolves a copy of the universe for `generations` generations and reports the known
+    /// `ships` which leave the rectangle from `min` to `max`, as emitted by guns and puffers.
+    ///
+    /// A spaceship escapes once it is isolated, outside of the rectangle and moving away
+    /// from it. It is then removed from the copy, so it is only reported once. Symmetric
+    /// spaceships are always reported in the first orientation of [`Transform::ALL`]
+    /// that fits, so their lanes and timings are comparable.
+    pub fn escapes(
+        &self,
+        ships: &[Spaceship],
+        min: Position,
+        max: Position,
+        generations: usize,
+    ) -> EscapeReport {
+        let mut universe = self.clone();
+        let mut report = EscapeReport::default();
+        for step in 0..=generations {
+            universe.remove_escapes(ships, min, max, &mut report);
+            if step < generations {
+                universe.evolve();
+            }
+        }
+        report
+    }
+
+    // the phase, position and orientation of each isolated copy of `ship`, sorted by the
+    // orientation's index in `Transform::ALL` and the phase. symmetric copies show up once
+    // for each way they fit.
+    pub(crate) fn spaceships(&self, ship: &Spaceship) -> Vec<(usize, Position, Transform)> {
+        sorted_by_orientation(ship, |pattern| self.find(pattern, &Transform::ALL))
+    }
+
+    // removes the `ships` leaving the rectangle from `min` to `max` in this generation, and
+    // adds them to `report`
+    pub(crate) fn remove_escapes(
+        &mut self,
+        ships: &[Spaceship],
+        min: Position,
+        max: Position,
+        report: &mut EscapeReport,
+    ) {
+        // leaving spaceships are outside of the rectangle, so only the cells there are looked
+        // at, and nothing at all while the rectangle holds every cell
+        if self.population_in(min, max) == self.population() {
+            return;
+        }
+        let mut alive = self.cell_map();
+        let outside: Vec<Position> = alive
+            .keys()
+            .map(|&(x, y)| Position::new(x, y))
+            .filter(|pos| pos.x < min.x || pos.x > max.x || pos.y < min.y || pos.y > max.y)
+            .collect();
+        for ship in ships {
+            for (phase, pos, transform) in spaceships_around(&alive, ship, &outside) {
+                let cells: Vec<Position> = ship.phases[phase]
+                    .cells()
+                    .iter()
+                    .map(|&(offset, _)| pos + transform.apply(offset))
+                    .collect();
+                // another orientation of a symmetric spaceship came first
+                if cells
+                    .iter()
+                    .any(|cell| !alive.contains_key(&(cell.x, cell.y)))
+                {
+                    continue;
+                }
+                let velocity = transform.apply(ship.displacement);
+                let leaving = cells.iter().all(|cell| cell.x > max.x) && velocity.dx > 0
+                    || cells.iter().all(|cell| cell.x < min.x) && velocity.dx < 0
+                    || cells.iter().all(|cell| cell.y > max.y) && velocity.dy > 0
+                    || cells.iter().all(|cell| cell.y < min.y) && velocity.dy < 0;
+                if !leaving {
+                    continue;
+                }
+                for &cell in &cells {
+                    self.set_cell(cell, Cell::DEAD);
+                    alive.remove(&(cell.x, cell.y));
+                }
+
+                let (lane, timing) =
+                    lane_and_timing(pos, phase, self.generation(), velocity, ship.period());
+                report.escapes.push(Escape {
+                    ship: ship.name.clone(),
+                    generation: self.generation(),
+                    position: pos,
+                    transform,
+                    phase,
+                    velocity,
+                    period: ship.period(),
+                    lane,
+                    timing,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use std::{fmt, str::FromStr};
+
+use crate::{
+    core::{Cell, Offset, Position},
+    rule::{prefixed, ParseRuleError, Rule},
+    store::SharedStore,
+    table::Table,
+    universe::{Id, Universe},
+};
+
+/// How the edges of a [`BoundedGrid`] are joined, following Golly's bounded grids.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Topology {
+    /// Nothing is joined, the cells beyond the edges are always dead.
+    Plane,
+    /// Opposite edges are joined.
+    Torus,
+    /// Opposite edges are joined, one pair of them with a twist: the top and bottom edges
+    /// if `horizontal_twist` is set, else the left and right edges.
+    KleinBottle { horizontal_twist: bool },
+    /// Opposite edges are joined, both pairs with a twist.
+    CrossSurface,
+    /// The top edge is joined to the left one and the bottom edge to the right one,
+    /// which needs a square grid.
+    Sphere,
+}
+
+// the side of the squares of the border which are skipped at once when the cells they are
+// joined with are dead
+const JOIN_CHUNK: i64 = 64;
+
+/// Grid of `width` x `height` cells, roughly centered on the origin, outside of which
+/// there are no cells.
+///
+/// The edges are fed into the hashlife recursion by copying the cells they are joined with
+/// into a border around the grid, as wide as the radius of the rule, before every
+/// generation, and by clearing everything outside of the grid afterwards. Memoized results
+/// don't depend on the grid, so a store can be shared with universes on other grids.
+///
+/// Parses and prints Golly's notation, for example `T64,32` for a torus, `P64,32` for a
+/// plane, `K64*,32` for a Klein bottle with a horizontal twist, `C64,32` for a cross-surface
+/// and `S64` for a sphere.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BoundedGrid {
+    pub topology: Topology,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BoundedGrid {
+    pub fn new(topology: Topology, width: u32, height: u32) -> Self {
+        assert!(width > 0 && height > 0, "a grid needs cells");
+        assert!(
+            topology != Topology::Sphere || width == height,
+            "a sphere needs a square grid"
+        );
+        Self {
+            topology,
+            width,
+            height,
+        }
+    }
+
+    /// The north west and the south east corner of the grid.
+    pub fn corners(&self) -> (Position, Position) {
+        let min = Position::new(-i64::from(self.width / 2), -i64::from(self.height / 2));
+        let max = min + Offset::new(i64::from(self.width) - 1, i64::from(self.height) - 1);
+        (min, max)
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        let (min, max) = self.corners();
+        (min.x..=max.x).contains(&pos.x) && (min.y..=max.y).contains(&pos.y)
+    }
+
+    // the rectangles around the grid up to `width` cells away from it, split into squares
+    // of at most `JOIN_CHUNK` cells, within which the cells are joined with a rectangle
+    fn border(&self, width: i64) -> Vec<(Position, Position)> {
+        let (min, max) = self.corners();
+        let columns = [
+            (min.x - width, min.x - 1),
+            (min.x, max.x),
+            (max.x + 1, max.x + width),
+        ];
+        let rows = [
+            (min.y - width, min.y - 1),
+            (min.y, max.y),
+            (max.y + 1, max.y + width),
+        ];
+        let chunks = |(first, last): (i64, i64)| {
+            (first..=last)
+                .step_by(JOIN_CHUNK as usize)
+                .map(move |start| (start, (start + JOIN_CHUNK - 1).min(last)))
+        };
+        let mut border = Vec::new();
+        for (i, &row) in rows.iter().enumerate() {
+            for (j, &column) in columns.iter().enumerate() {
+                // the grid itself
+                if (i, j) == (1, 1) {
+                    continue;
+                }
+                for (y0, y1) in chunks(row) {
+                    for (x0, x1) in chunks(column) {
+                        border.push((Position::new(x0, y0), Position::new(x1, y1)));
+                    }
+                }
+            }
+        }
+        border
+    }
+
+    // the cell of the grid a cell around it copies, `None` if it stays dead
+    fn joined(&self, pos: Position) -> Option<Position> {
+        self.wrapped(pos).filter(|&joined| self.contains(joined))
+    }
+
+    // where a cell around the grid is taken to by joining the edges, which may be outside of
+    // the grid for the corners. the corners follow from joining the left and right edges first.
+    fn wrapped(&self, pos: Position) -> Option<Position> {
+        let (min, max) = self.corners();
+        let (width, height) = (i64::from(self.width), i64::from(self.height));
+        let mirror_x = |x: i64| min.x + max.x - x;
+        let mirror_y = |y: i64| min.y + max.y - y;
+        let (mut x, mut y) = (pos.x, pos.y);
+        let (twist_columns, twist_rows) = match self.topology {
+            Topology::Plane => return None,
+            Topology::Sphere => return Some(self.wrapped_on_sphere(pos)),
+            Topology::Torus => (false, false),
+            Topology::KleinBottle { horizontal_twist } => (!horizontal_twist, horizontal_twist),
+            Topology::CrossSurface => (true, true),
+        };
+
+        if x < min.x || x > max.x {
+            x += if x < min.x { width } else { -width };
+            if twist_columns {
+                y = mirror_y(y);
+            }
+        }
+        if y < min.y || y > max.y {
+            y += if y < min.y { height } else { -height };
+            if twist_rows {
+                x = mirror_x(x);
+            }
+        }
+        Some(Position::new(x, y))
+    }
+
+    fn wrapped_on_sphere(&self, pos: Position) -> Position {
+        let (min, max) = self.corners();
+        // the distance to an edge becomes the distance to the edge it is joined with,
+        // and the offset along it stays the same
+        if pos.x < min.x && pos.y < min.y {
+            Position::new(2 * min.x - 1 - pos.x, 2 * min.y - 1 - pos.y)
+        } else if pos.x > max.x && pos.y > max.y {
+            Position::new(2 * max.x + 1 - pos.x, 2 * max.y + 1 - pos.y)
+        } else if pos.x < min.x {
+            Position::new(min.x + (pos.y - min.y), min.y + (min.x - pos.x) - 1)
+        } else if pos.x > max.x {
+            Position::new(min.x + (pos.y - min.y), max.y - (pos.x - max.x) + 1)
+        } else if pos.y < min.y {
+            Position::new(min.x + (min.y - pos.y) - 1, min.y + (pos.x - min.x))
+        } else {
+            // the two other corners would need cells beyond the edges, and end up outside
+            Position::new(max.x - (pos.y - max.y) + 1, min.y + (pos.x - min.x))
+        }
+    }
+
+    // copies the joined cells into a border around the grid as wide as the radius of the rule
+    pub(crate) fn join_edges(&self, table: &mut impl Table, mut root: Id) -> Id {
+        let radius = i64::from(table.rule().radius());
+        let (min, max) = self.corners();
+        let margin = Offset::new(radius, radius);
+        let (min, max) = (min - margin, max + margin);
+        while !min.in_bounds(root.node(table).level()) || !max.in_bounds(root.node(table).level()) {
+            root = table.expand(root);
+        }
+
+        // only the cells copied from subtrees which aren't empty are looked at
+        let corner = root.node(table).level().min_pos();
+        let mut copies = Vec::new();
+        for (first, last) in self.border(radius) {
+            let ends = [
+                first,
+                last,
+                Position::new(first.x, last.y),
+                Position::new(last.x, first.y),
+            ];
+            // the cells are moved, mirrored or turned as a whole, so the corners of the
+            // rectangle they are copied from are where its corners are taken to
+            let wrapped: Vec<Position> = ends.iter().filter_map(|&end| self.wrapped(end)).collect();
+            let (grid_min, grid_max) = self.corners();
+            let min = Position::new(
+                wrapped
+                    .iter()
+                    .map(|pos| pos.x)
+                    .min()
+                    .unwrap_or(i64::MAX)
+                    .max(grid_min.x),
+                wrapped
+                    .iter()
+                    .map(|pos| pos.y)
+                    .min()
+                    .unwrap_or(i64::MAX)
+                    .max(grid_min.y),
+            );
+            let max = Position::new(
+                wrapped
+                    .iter()
+                    .map(|pos| pos.x)
+                    .max()
+                    .unwrap_or(i64::MIN)
+                    .min(grid_max.x),
+                wrapped
+                    .iter()
+                    .map(|pos| pos.y)
+                    .max()
+                    .unwrap_or(i64::MIN)
+                    .min(grid_max.y),
+            );
+            if min.x > max.x || min.y > max.y || table.population_in(root, corner, min, max) == 0 {
+                continue;
+            }
+            for y in first.y..=last.y {
+                for x in first.x..=last.x {
+                    let pos = Position::new(x, y);
+                    if let Some(joined) = self.joined(pos) {
+                        let cell = table.get_tree_cell(root, joined);
+                        if !cell.is_dead() {
+                            copies.push((pos, cell));
+                        }
+                    }
+                }
+            }
+        }
+        table.set_cells(root, corner, &mut copies)
+    }
+
+    // clears all cells outside of the grid
+    pub(crate) fn clip(&self, table: &mut impl Table, root: Id) -> Id {
+        let (min, max) = self.corners();
+        let level = root.node(table).level();
+        table.clip(root, level.min_pos(), min, max)
+    }
+}
+
+impl Topology {
+    fn letter(self) -> char {
+        match self {
+            Topology::Plane => 'P',
+            Topology::Torus => 'T',
+            Topology::KleinBottle { .. } => 'K',
+            Topology::CrossSurface => 'C',
+            Topology::Sphere => 'S',
+        }
+    }
+}
+
+impl fmt::Display for BoundedGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width_twist, height_twist) = match self.topology {
+            Topology::KleinBottle { horizontal_twist } => (horizontal_twist, !horizontal_twist),
+            _ => (false, false),
+        };
+        let twist = |twisted| if twisted { "*" } else { "" };
+        write!(
+            f,
+            "{}{}{}",
+            self.topology.letter(),
+            self.width,
+            twist(width_twist)
+        )?;
+        if self.topology != Topology::Sphere {
+            write!(f, ",{}{}", self.height, twist(height_twist))?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses Golly's notation, where a single size makes a square grid.
+impl FromStr for BoundedGrid {
+    type Err = ParseRuleError;
+
+    fn from_str(grid: &str) -> Result<Self, Self::Err> {
+        let letters = ['P', 'T', 'K', 'C', 'S'];
+        let sizes = prefixed(grid.trim(), &letters)
+            .ok_or_else(|| ParseRuleError::new(grid, "expected one of P, T, K, C or S"))?;
+        let letter = grid.trim().chars().next().unwrap().to_ascii_uppercase();
+
+        // a size with an optional `*` for a twisted edge
+        let parse_size = |size: &str| {
+            let (size, twisted) = match size.strip_suffix('*') {
+                Some(size) => (size, true),
+                None => (size, false),
+            };
+            match size.parse::<u32>() {
+                Ok(size) if size > 0 => Ok((size, twisted)),
+                _ => Err(ParseRuleError::new(grid, "expected a positive size")),
+            }
+        };
+        let ((width, width_twist), (height, height_twist)) = match sizes.split_once(',') {
+            Some((width, height)) => (parse_size(width)?, parse_size(height)?),
+            None => {
+                let size = parse_size(sizes)?;
+                (size, size)
+            }
+        };
+
+        let topology = match (letter, width_twist, height_twist) {
+            ('K', true, false) => Topology::KleinBottle {
+                horizontal_twist: true,
+            },
+            ('K', false, true) => Topology::KleinBottle {
+                horizontal_twist: false,
+            },
+            ('K', _, _) => {
+                return Err(ParseRuleError::new(
+                    grid,
+                    "a Klein bottle needs exactly one twisted pair of edges",
+                ))
+            }
+            (_, true, _) | (_, _, true) => {
+                return Err(ParseRuleError::new(
+                    grid,
+                    "only Klein bottles have a single twist",
+                ))
+            }
+            ('P', _, _) => Topology::Plane,
+            ('T', _, _) => Topology::Torus,
+            ('C', _, _) => Topology::CrossSurface,
+            _ if width != height => {
+                return Err(ParseRuleError::new(grid, "a sphere needs a square grid"))
+            }
+            _ => Topology::Sphere,
+        };
+        Ok(Self::new(topology, width, height))
+    }
+}
+
+impl Universe {
+    /// Creates an empty universe running `rule` on a bounded `grid`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rule` has `B0`, whose alive background would fill the grid, if its radius
+    /// is larger than the grid, or if it is a Margolus rule and the grid can't be partitioned
+    /// into blocks.
+    pub fn with_grid(rule: Rule, grid: BoundedGrid) -> Self {
+        Self::with_store_and_grid(&SharedStore::with_rule(rule), grid)
+    }
+
+    /// Creates an empty universe on a bounded `grid` whose nodes and memoized results live in
+    /// `store`, so that it shares them with the other universes in it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rule of `store` has `B0`, whose alive background would fill the grid, if
+    /// its radius is larger than the grid, or if it is a Margolus rule and the grid can't be
+    /// partitioned into blocks.
+    pub fn with_store_and_grid(store: &SharedStore, grid: BoundedGrid) -> Self {
+        {
+            let store = store.lock();
+            let rule = store.rule();
+            assert!(
+                rule.background(1) == Cell::DEAD,
+                "bounded grids don't support B0 rules"
+            );
+            assert!(
+                rule.radius() <= grid.width.min(grid.height),
+                "the radius of the rule is larger than the grid"
+            );
+            assert!(
+                !rule.is_margolus() || (grid.width | grid.height) & 1 == 0,
+                "Margolus rules need a grid of even width and height"
+            );
+        }
+        Self {
+            grid: Some(grid),
+            ..Self::with_store(store)
+        }
+    }
+
+    /// The bounded grid the universe lives on, `None` for the infinite plane.
+    pub fn grid(&self) -> Option<BoundedGrid> {
+        self.grid
+    }
+}
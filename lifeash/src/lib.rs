@@ -1,8 +1,47 @@
+pub mod budget;
+pub mod collision;
 pub mod core;
+pub mod dense;
+pub mod engine;
+pub mod escape;
+pub mod grid;
+pub mod line;
 pub mod node;
+#[cfg(feature = "parallel")]
+mod parallel;
+pub mod pattern;
+pub mod predecessor;
+pub mod range;
+pub mod rule;
+pub mod rule_file;
+pub mod space;
+pub mod store;
+pub mod symmetry;
+mod table;
+pub mod tiled;
 pub mod universe;
+pub mod view;
 
 pub use crate::{
+    budget::{Budget, CancelToken, Interrupted},
+    collision::{
+        Collision, CollisionSearch, CollisionTable, NotASpaceship, Outcome, Placement, Product,
+    },
     core::{Cell, Offset, Position},
+    dense::DenseGrid,
+    engine::Engine,
+    escape::{Escape, EscapeReport, Spaceship},
+    grid::{BoundedGrid, Topology},
+    line::{LineRule, LineUniverse},
+    pattern::{Pattern, Transform},
+    predecessor::PredecessorSearch,
+    range::{RuleFamily, RuleRange},
+    rule::{Neighborhood, ParseRuleError, Rule},
+    rule_file::{Icons, RuleFile},
+    space::{Position3, SpaceRule, SpaceUniverse},
+    store::{NodeStore, SharedStore},
+    symmetry::{PhaseSymmetry, Symmetry},
+    tiled::TiledUniverse,
     universe::Universe,
+    view::UniverseView,
 };
@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+
+use crate::{
+    core::{Cell, Level, Position},
+    rule::Rule,
+    universe::{Id, Universe},
+};
+
+/// Rule of a one-dimensional cellular automaton, where the next state of a cell depends on
+/// itself and its left and right neighbor.
+///
+/// Elementary rules use Wolfram's numbering from 0 to 255, like rule 30 or rule 110.
+/// Totalistic rules with `k` colors use Wolfram's code, whose base `k` digits give the next
+/// state for each sum of the three cells, starting with the least significant digit for
+/// the sum 0.
+///
+/// Rules which turn an empty neighborhood alive fill the infinite line. They are run like
+/// `B0` rules on a [`Universe`]: the line stores the difference of each cell to a uniform
+/// background, which evolves by the rule as well, see [`LineUniverse::background`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LineRule {
+    colors: u8,
+    kind: LineKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum LineKind {
+    Elementary(u8),
+    // the next state for each sum of the three cells
+    Totalistic(Vec<u8>),
+}
+
+impl LineRule {
+    /// Creates Wolfram's elementary rule `number`.
+    pub fn elementary(number: u8) -> Self {
+        Self {
+            colors: 2,
+            kind: LineKind::Elementary(number),
+        }
+    }
+
+    /// Creates the totalistic rule with Wolfram's `code` for `colors` colors.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are less than two colors, or if `code` has more digits than there are
+    /// sums.
+    pub fn totalistic(code: u128, colors: u8) -> Self {
+        assert!(colors >= 2, "a rule needs at least two colors");
+        let sums = 3 * (u32::from(colors) - 1) + 1;
+        if let Some(limit) = u128::from(colors).checked_pow(sums) {
+            assert!(code < limit, "the code has more digits than there are sums");
+        }
+        let table: Vec<u8> = (0..sums)
+            .map(|sum| {
+                let place = u128::from(colors).checked_pow(sum);
+                place.map_or(0, |place| (code / place % u128::from(colors)) as u8)
+            })
+            .collect();
+        Self {
+            colors,
+            kind: LineKind::Totalistic(table),
+        }
+    }
+
+    pub fn colors(&self) -> u8 {
+        self.colors
+    }
+
+    /// The state a cell in state `center` between `left` and `right` has next.
+    pub fn next(&self, left: Cell, center: Cell, right: Cell) -> Cell {
+        match &self.kind {
+            LineKind::Elementary(number) => {
+                let index = (left.state() << 2) | (center.state() << 1) | right.state();
+                Cell::new(number >> index & 1)
+            }
+            LineKind::Totalistic(table) => {
+                let sum = [left, center, right]
+                    .iter()
+                    .map(|cell| usize::from(cell.state()))
+                    .sum::<usize>();
+                Cell::new(table[sum])
+            }
+        }
+    }
+
+    // the difference of `cell` to `background`, which is how the nodes store it
+    fn relative(&self, cell: Cell, background: Cell) -> Cell {
+        Cell::new((cell.state() + self.colors - background.state()) % self.colors)
+    }
+
+    // the cell whose difference to `background` is `relative`
+    fn absolute(&self, relative: Cell, background: Cell) -> Cell {
+        Cell::new((relative.state() + background.state()) % self.colors)
+    }
+}
+
+impl Default for LineRule {
+    fn default() -> Self {
+        Self::elementary(110)
+    }
+}
+
+// hash-consed like the nodes of a `Universe`, with two halves instead of four quadrants
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum LineNode {
+    Leaf(Cell),
+    Inode {
+        level: Level,
+        population: u32,
+        left: Id,
+        right: Id,
+    },
+}
+
+impl LineNode {
+    fn level(self) -> Level {
+        match self {
+            LineNode::Leaf(_) => Level::LEAF_LEVEL,
+            LineNode::Inode { level, .. } => level,
+        }
+    }
+
+    fn population(self) -> u32 {
+        match self {
+            LineNode::Leaf(cell) => !cell.is_dead() as u32,
+            LineNode::Inode { population, .. } => population,
+        }
+    }
+
+    fn halves(self) -> (Id, Id) {
+        match self {
+            LineNode::Inode { left, right, .. } => (left, right),
+            LineNode::Leaf(_) => panic!("not an inode"),
+        }
+    }
+}
+
+/// A pattern on the infinite line together with its generation, evolved by hashlife on a
+/// binary tree.
+///
+/// Like [`Universe`], every call to [`LineUniverse::evolve`] advances a single generation,
+/// and the nodes and their memoized results are kept for all later generations.
+#[derive(Debug, Clone)]
+pub struct LineUniverse {
+    rule: LineRule,
+    nodes: Vec<LineNode>,
+    ids: HashMap<LineNode, Id>,
+    // the center half of a node one generation later, for each background it can be in
+    results: HashMap<(Cell, Id), Id>,
+    root: Id,
+    generation: usize,
+    // the state of all cells outside of the pattern, which the nodes store the difference to
+    background: Cell,
+}
+
+impl LineUniverse {
+    /// Creates an empty line running `rule`.
+    pub fn new(rule: LineRule) -> Self {
+        let mut universe = Self {
+            rule,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            results: HashMap::new(),
+            root: Id(0),
+            generation: 0,
+            background: Cell::DEAD,
+        };
+        universe.root = universe.new_empty_tree(Level::new(3));
+        universe
+    }
+
+    pub fn rule(&self) -> &LineRule {
+        &self.rule
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// State of all cells outside of the pattern. It only turns alive with rules which turn
+    /// an empty neighborhood alive, and then follows the rule from there.
+    pub fn background(&self) -> Cell {
+        self.background
+    }
+
+    /// Number of cells which differ from the background.
+    pub fn population(&self) -> u32 {
+        self.node(self.root).population()
+    }
+
+    pub fn set_cell(&mut self, x: i64, cell: Cell) {
+        assert!(
+            cell.state() < self.rule.colors,
+            "the rule has no such color"
+        );
+        while !self.node(self.root).level().coord_range().contains(&x) {
+            self.root = self.expand(self.root);
+        }
+        let cell = self.rule.relative(cell, self.background);
+        self.root = self.set_tree_cell(self.root, x, cell);
+    }
+
+    pub fn get_cell(&self, x: i64) -> Cell {
+        let mut tree = self.root;
+        if !self.node(tree).level().coord_range().contains(&x) {
+            return self.background;
+        }
+        // the offset of `x` from the start of `tree`
+        let mut offset = x - self.node(tree).level().min_coord();
+        loop {
+            match self.node(tree) {
+                LineNode::Leaf(cell) => return self.rule.absolute(cell, self.background),
+                LineNode::Inode {
+                    level, left, right, ..
+                } => {
+                    let half = (level.side_len() / 2) as i64;
+                    tree = if offset < half { left } else { right };
+                    offset %= half;
+                }
+            }
+        }
+    }
+
+    /// Positions of all cells which differ from the background, from left to right.
+    pub fn alive_cells(&self) -> Vec<i64> {
+        let mut cells = Vec::new();
+        let min = self.node(self.root).level().min_coord();
+        self.for_each_alive(self.root, min, &mut |x| cells.push(x));
+        cells
+    }
+
+    pub fn evolve(&mut self) {
+        let mut root = self.root;
+        // the result of the root is its center half, so the population has to stay within
+        // the center quarter to have room to grow by a cell on both sides
+        loop {
+            let node = self.node(root);
+            if node.level() >= 3 {
+                let (left, right) = node.halves();
+                let inner_left = self.node(self.node(left).halves().1).halves().1;
+                let inner_right = self.node(self.node(right).halves().0).halves().0;
+                if self.node(left).population() == self.node(inner_left).population()
+                    && self.node(right).population() == self.node(inner_right).population()
+                {
+                    break;
+                }
+            }
+            root = self.expand(root);
+        }
+
+        self.root = self.evolve_tree(root);
+        self.generation += 1;
+        let background = self.background;
+        self.background = self.rule.next(background, background, background);
+    }
+
+    /// Renders the spacetime diagram of the next `generations` generations into a
+    /// [`Universe`], with the current generation in row 0 and each later one in the row below,
+    /// evolving this line along the way.
+    ///
+    /// The universe runs a rule without births or survivals, it is only meant for viewing.
+    /// Only the cells differing from the background are rendered.
+    pub fn spacetime(&mut self, generations: usize) -> Universe {
+        let mut universe = Universe::with_rule(Rule::new(&[], &[], self.rule.colors));
+        universe.initialize();
+        for row in 0..=generations {
+            for x in self.alive_cells() {
+                universe.set_cell(Position::new(x, row as i64), self.get_cell(x));
+            }
+            if row < generations {
+                self.evolve();
+            }
+        }
+        universe
+    }
+
+    fn node(&self, id: Id) -> LineNode {
+        self.nodes[id.0]
+    }
+
+    fn get_id(&mut self, node: LineNode) -> Id {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = Id(self.nodes.len());
+        self.nodes.push(node);
+        self.ids.insert(node, id);
+        id
+    }
+
+    fn new_leaf(&mut self, cell: Cell) -> Id {
+        self.get_id(LineNode::Leaf(cell))
+    }
+
+    fn new_inode(&mut self, left: Id, right: Id) -> Id {
+        let (left_node, right_node) = (self.node(left), self.node(right));
+        debug_assert!(left_node.level() == right_node.level());
+        self.get_id(LineNode::Inode {
+            level: left_node.level() + 1,
+            population: left_node.population() + right_node.population(),
+            left,
+            right,
+        })
+    }
+
+    fn new_empty_tree(&mut self, level: Level) -> Id {
+        if level == Level::LEAF_LEVEL {
+            self.new_leaf(Cell::DEAD)
+        } else {
+            let half = self.new_empty_tree(level - 1);
+            self.new_inode(half, half)
+        }
+    }
+
+    // wraps `tree` into a tree one level higher, keeping it centered
+    fn expand(&mut self, tree: Id) -> Id {
+        let node = self.node(tree);
+        let (left, right) = node.halves();
+        let border = self.new_empty_tree(node.level() - 1);
+        let (left, right) = (self.new_inode(border, left), self.new_inode(right, border));
+        self.new_inode(left, right)
+    }
+
+    // sets the cell at `x`, relative to the center of `tree`
+    fn set_tree_cell(&mut self, tree: Id, x: i64, cell: Cell) -> Id {
+        match self.node(tree) {
+            LineNode::Leaf(_) => self.new_leaf(cell),
+            LineNode::Inode {
+                level, left, right, ..
+            } => {
+                // the centers of the halves are a quarter of the side away
+                let quarter = (level.side_len() / 4) as i64;
+                if x < 0 {
+                    let left = self.set_tree_cell(left, x + quarter, cell);
+                    self.new_inode(left, right)
+                } else {
+                    let right = self.set_tree_cell(right, x - quarter, cell);
+                    self.new_inode(left, right)
+                }
+            }
+        }
+    }
+
+    // calls `f` with the position of every cell in `tree`, which starts at `min`, that differs
+    // from the background
+    fn for_each_alive(&self, tree: Id, min: i64, f: &mut impl FnMut(i64)) {
+        match self.node(tree) {
+            LineNode::Leaf(cell) => {
+                if !cell.is_dead() {
+                    f(min)
+                }
+            }
+            LineNode::Inode {
+                level,
+                population,
+                left,
+                right,
+            } => {
+                if population != 0 {
+                    let half = (level.side_len() / 2) as i64;
+                    self.for_each_alive(left, min, f);
+                    self.for_each_alive(right, min + half, f);
+                }
+            }
+        }
+    }
+
+    // the center half of `tree`, which is at least at level 2, one generation later
+    fn evolve_tree(&mut self, tree: Id) -> Id {
+        let background = self.background;
+        if let Some(&result) = self.results.get(&(background, tree)) {
+            return result;
+        }
+
+        let node = self.node(tree);
+        let result = if node.population() == 0 {
+            self.new_empty_tree(node.level() - 1)
+        } else if node.level() == 2 {
+            let (left, right) = node.halves();
+            let (a, b) = self.node(left).halves();
+            let (c, d) = self.node(right).halves();
+            let cells = [a, b, c, d].map(|leaf| match self.node(leaf) {
+                LineNode::Leaf(cell) => self.rule.absolute(cell, background),
+                LineNode::Inode { .. } => unreachable!(),
+            });
+            let next_background = self.rule.next(background, background, background);
+            let left = self.rule.next(cells[0], cells[1], cells[2]);
+            let right = self.rule.next(cells[1], cells[2], cells[3]);
+            let (left, right) = (
+                self.rule.relative(left, next_background),
+                self.rule.relative(right, next_background),
+            );
+            let (left, right) = (self.new_leaf(left), self.new_leaf(right));
+            self.new_inode(left, right)
+        } else {
+            // the three centered subtrees a quarter of the size of `tree`
+            let (left, right) = node.halves();
+            let (left_left, left_right) = self.node(left).halves();
+            let (right_left, right_right) = self.node(right).halves();
+            let first = self.centered(left_left, left_right);
+            let second = self.centered(left_right, right_left);
+            let third = self.centered(right_left, right_right);
+
+            let (first, second) = (self.new_inode(first, second), self.new_inode(second, third));
+            let (left, right) = (self.evolve_tree(first), self.evolve_tree(second));
+            self.new_inode(left, right)
+        };
+        self.results.insert((background, tree), result);
+        result
+    }
+
+    // the subtree of the same level centered between two adjacent ones
+    fn centered(&mut self, left: Id, right: Id) -> Id {
+        let inner_left = self.node(left).halves().1;
+        let inner_right = self.node(right).halves().0;
+        self.new_inode(inner_left, inner_right)
+    }
+}
@@ -5,7 +5,7 @@ use crate::{
     universe::Id,
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum Node {
     // always level 0
     Leaf(Leaf),
@@ -13,7 +13,7 @@ pub(crate) enum Node {
     Inode(Inode),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Copy, Clone)]
 pub(crate) struct Inode {
     pub(crate) level: Level,
     pub(crate) population: u32,
@@ -62,19 +62,17 @@ impl Leaf {
 
     #[allow(dead_code)]
     fn alive(self) -> bool {
-        match self.0 {
-            Cell::Dead => false,
-            Cell::Alive => true,
-        }
+        self.0 == Cell::ALIVE
     }
 }
 
 impl Node {
+    // counts the cells which aren't dead, whatever state they are in
     #[inline(always)]
     pub(crate) fn population(&self) -> u32 {
         match *self {
             Node::Inode(ref i) => i.population,
-            Node::Leaf(c) => c.0 as u32,
+            Node::Leaf(c) => !c.0.is_dead() as u32,
         }
     }
 
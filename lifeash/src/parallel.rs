@@ -0,0 +1,171 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::Mutex,
+};
+
+use crate::{
+    core::Level,
+    node::Node,
+    rule::Rule,
+    store::NodeStore,
+    table::{ReadTable, Table},
+    universe::{Id, Universe},
+};
+
+const SHARD_COUNT: usize = 64;
+
+// below this level a quadrant recursion is too cheap to be worth a rayon task
+const FORK_LEVEL: Level = Level::new(8);
+
+// concurrent node store used during a parallel evolution.
+// the nodes already in the node store are read lock-free from the frozen `base`,
+// new nodes are interned into shards chosen by their hash. each shard hands out every
+// `SHARD_COUNT`th id after the ones of the store, so an id leads back to its shard and
+// interning a node only takes the lock of one shard.
+struct ShardedTable<'a> {
+    base: &'a NodeStore,
+    // the first id of the new nodes
+    start: usize,
+    shards: Vec<Mutex<Shard>>,
+}
+
+#[derive(Default)]
+struct Shard {
+    interned: HashMap<Node, Id>,
+    // the nodes in the order of their ids
+    nodes: Vec<Node>,
+    // the results of the nodes of the shard, or of nodes of the store whose id leads here
+    results: HashMap<Id, Id>,
+}
+
+impl<'a> ShardedTable<'a> {
+    fn new(base: &'a NodeStore) -> Self {
+        Self {
+            base,
+            start: base.next_id,
+            shards: (0..SHARD_COUNT).map(|_| Mutex::default()).collect(),
+        }
+    }
+
+    // the shard of `id` and the index of its node there, which only exists for new nodes
+    fn locate(&self, id: Id) -> (&Mutex<Shard>, usize) {
+        let offset = id.0.wrapping_sub(self.start);
+        (&self.shards[offset % SHARD_COUNT], offset / SHARD_COUNT)
+    }
+
+    // collects all new nodes and memoized results, so they can be moved into the node store,
+    // together with the id after all new ones
+    fn into_new_entries(self) -> (Vec<(Id, Node)>, HashMap<Id, Id>, usize) {
+        let (mut nodes, mut results, mut end) = (Vec::new(), HashMap::new(), self.start);
+        for (index, shard) in self.shards.into_iter().enumerate() {
+            let shard = shard.into_inner().unwrap();
+            for (i, node) in shard.nodes.into_iter().enumerate() {
+                let id = self.start + i * SHARD_COUNT + index;
+                end = end.max(id + 1);
+                nodes.push((Id(id), node));
+            }
+            results.extend(shard.results);
+        }
+        (nodes, results, end)
+    }
+}
+
+impl ReadTable for &ShardedTable<'_> {
+    fn node(&self, id: Id) -> Node {
+        match self.base.table.get_by_left(&id) {
+            Some(node) => *node,
+            None => {
+                let (shard, index) = self.locate(id);
+                shard.lock().unwrap().nodes[index]
+            }
+        }
+    }
+}
+
+impl Table for &ShardedTable<'_> {
+    fn rule(&self) -> &Rule {
+        Table::rule(self.base)
+    }
+
+    fn get_id(&mut self, node: Node) -> Id {
+        if let Some(id) = self.base.table.get_by_right(&node) {
+            return *id;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        node.hash(&mut hasher);
+        let index = hasher.finish() as usize % SHARD_COUNT;
+
+        let mut shard = self.shards[index].lock().unwrap();
+        if let Some(id) = shard.interned.get(&node) {
+            return *id;
+        }
+        // the node is published by id in the same shard, so nobody can observe the id
+        // without being able to resolve it
+        let id = Id(self.start + shard.nodes.len() * SHARD_COUNT + index);
+        shard.nodes.push(node);
+        shard.interned.insert(node, id);
+        id
+    }
+
+    fn result(&self, id: Id) -> Option<Id> {
+        let (shard, _) = self.locate(id);
+        let shard = shard.lock().unwrap();
+        shard.results.get(&id).copied().or_else(|| {
+            // nodes interned during this evolution aren't in the store
+            self.base.table.get_by_left(&id)?;
+            self.base.result(id)
+        })
+    }
+
+    fn set_result(&mut self, id: Id, result: Id) {
+        let (shard, _) = self.locate(id);
+        shard.lock().unwrap().results.insert(id, result);
+    }
+}
+
+fn par_evolve_tree(table: &ShardedTable, tree: Id) -> Id {
+    let mut table = table;
+    let level = tree.inode(&table).level;
+    if level < FORK_LEVEL || level == table.base_level() {
+        return table.evolve_tree(tree);
+    }
+    if let Some(result) = table.result(tree) {
+        return result;
+    }
+
+    let (nw, ne, sw, se) = table.overlapping_subs(tree);
+    let ((nw, ne), (sw, se)) = rayon::join(
+        || rayon::join(|| par_evolve_tree(table, nw), || par_evolve_tree(table, ne)),
+        || rayon::join(|| par_evolve_tree(table, sw), || par_evolve_tree(table, se)),
+    );
+    let result = table.new_inode(nw, ne, sw, se);
+    table.set_result(tree, result);
+    result
+}
+
+impl Universe {
+    /// Evolves the universe by one generation like [`Universe::evolve`],
+    /// but recurses into the four quadrants of big nodes on the rayon thread pool.
+    pub fn par_evolve(&mut self) {
+        let mut store = self.store.lock_for_evolution(self.generation);
+        let root = self.join_edges(&mut *store);
+        let root = store.expand_for_evolution(root);
+
+        let sharded = ShardedTable::new(&store);
+        let result = par_evolve_tree(&sharded, root);
+        let (nodes, results, end) = sharded.into_new_entries();
+        for (id, node) in nodes {
+            store.table.insert(id, node);
+            store.log.insert(id, node);
+        }
+        store.next_id = end;
+        for (id, result) in results {
+            store.set_result(id, result);
+        }
+
+        self.root = Some(self.clip(&mut *store, result));
+        self.generation += 1;
+    }
+}
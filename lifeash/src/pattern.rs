@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+
+use crate::{
+    core::{Cell, Offset, Position},
+    node::Node,
+    table::ReadTable,
+    universe::Universe,
+};
+
+/// One of the eight ways to rotate or reflect a pattern onto the square grid.
+///
+/// Rotations are clockwise, with `y` growing to the south.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Transform {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirrors east and west.
+    FlipHorizontal,
+    /// Mirrors north and south.
+    FlipVertical,
+    /// Mirrors along the diagonal from north west to south east.
+    FlipDiagonal,
+    /// Mirrors along the diagonal from north east to south west.
+    FlipAntidiagonal,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+        Transform::FlipDiagonal,
+        Transform::FlipAntidiagonal,
+    ];
+
+    /// Where the cell at `offset` from the origin ends up.
+    pub fn apply(self, offset: Offset) -> Offset {
+        let Offset { dx, dy } = offset;
+        match self {
+            Transform::Identity => Offset::new(dx, dy),
+            Transform::Rotate90 => Offset::new(-dy, dx),
+            Transform::Rotate180 => Offset::new(-dx, -dy),
+            Transform::Rotate270 => Offset::new(dy, -dx),
+            Transform::FlipHorizontal => Offset::new(-dx, dy),
+            Transform::FlipVertical => Offset::new(dx, -dy),
+            Transform::FlipDiagonal => Offset::new(dy, dx),
+            Transform::FlipAntidiagonal => Offset::new(-dy, -dx),
+        }
+    }
+}
+
+/// A small pattern of cells at offsets from its origin, to look for in a [`Universe`].
+///
+/// Only the cells which aren't dead are stored, all others in the bounding box are dead.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Pattern {
+    // sorted by offset
+    cells: Vec<(Offset, Cell)>,
+}
+
+impl Pattern {
+    /// Creates a pattern of alive cells at the given offsets.
+    pub fn new(alive: &[(i64, i64)]) -> Self {
+        Self::with_cells(alive.iter().map(|&offset| (offset.into(), Cell::ALIVE)))
+    }
+
+    /// Creates a pattern from cells in any state, skipping the dead ones.
+    pub fn with_cells(cells: impl IntoIterator<Item = (Offset, Cell)>) -> Self {
+        let mut cells: Vec<_> = cells
+            .into_iter()
+            .filter(|(_, cell)| !cell.is_dead())
+            .collect();
+        cells.sort();
+        cells.dedup_by_key(|(offset, _)| *offset);
+        Self { cells }
+    }
+
+    /// The cells which aren't dead, sorted by their offset.
+    pub fn cells(&self) -> &[(Offset, Cell)] {
+        &self.cells
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// The pattern with every cell moved by `transform`, keeping the origin in place.
+    pub fn transformed(&self, transform: Transform) -> Self {
+        Self::with_cells(
+            self.cells
+                .iter()
+                .map(|&(offset, cell)| (transform.apply(offset), cell)),
+        )
+    }
+
+    /// North west and south east corner of the bounding box, relative to the origin.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is empty.
+    pub fn bounds(&self) -> (Offset, Offset) {
+        assert!(!self.is_empty(), "an empty pattern has no bounds");
+        let (mut min, mut max) = (self.cells[0].0, self.cells[0].0);
+        for &(offset, _) in &self.cells {
+            min = Offset::new(min.dx.min(offset.dx), min.dy.min(offset.dy));
+            max = Offset::new(max.dx.max(offset.dx), max.dy.max(offset.dy));
+        }
+        (min, max)
+    }
+
+    /// The pattern moved so that its bounding box starts at the origin, in the orientation
+    /// whose cells sort first. All rotations, reflections and translations of a pattern
+    /// share their canonical form.
+    pub fn canonical(&self) -> Self {
+        if self.is_empty() {
+            return Self::default();
+        }
+        Transform::ALL
+            .iter()
+            .map(|&transform| self.transformed(transform).normalized().0)
+            .min_by(|a, b| a.cells.cmp(&b.cells))
+            .unwrap()
+    }
+
+    /// A 128-bit digest of the cells which doesn't change when the pattern is moved.
+    ///
+    /// It is the 128-bit FNV-1a hash of the cells relative to the bounding box, each as
+    /// the little endian bytes of its offsets followed by its state, so it stays the same
+    /// across runs and machines and can be stored.
+    pub fn digest(&self) -> u128 {
+        const BASIS: u128 = 0x6c62_272e_07bb_0142_62b8_2175_6295_c58d;
+        const PRIME: u128 = 0x0000_0000_0100_0000_0000_0000_0000_013b;
+        let mut hash = BASIS;
+        if self.is_empty() {
+            return hash;
+        }
+        for (offset, cell) in self.normalized().0.cells {
+            let mut bytes = [0; 17];
+            bytes[..8].copy_from_slice(&offset.dx.to_le_bytes());
+            bytes[8..16].copy_from_slice(&offset.dy.to_le_bytes());
+            bytes[16] = cell.state();
+            for &byte in &bytes {
+                hash = (hash ^ u128::from(byte)).wrapping_mul(PRIME);
+            }
+        }
+        hash
+    }
+
+    /// Like [`Pattern::digest`], but also the same for all rotations and reflections.
+    pub fn canonical_digest(&self) -> u128 {
+        self.canonical().digest()
+    }
+
+    // the pattern moved so that its bounding box starts at the origin, and the offset
+    // it was moved by
+    pub(crate) fn normalized(&self) -> (Self, Offset) {
+        let (min, _) = self.bounds();
+        let cells = self
+            .cells
+            .iter()
+            .map(|&(offset, cell)| (offset - min, cell));
+        (Self::with_cells(cells), min)
+    }
+}
+
+impl Universe {
+    /// The cells which aren't dead as a pattern, at their offsets from the origin.
+    ///
+    /// With an alive background, the pattern describes the cells differing from it.
+    pub fn to_pattern(&self) -> Pattern {
+        let store = self.store.lock();
+        let root = self.root.unwrap();
+        let mut cells = Vec::new();
+        store.for_each_alive(root, root.node(&*store).level().min_pos(), &mut |pos| {
+            cells.push((Offset::new(pos.x, pos.y), store.get_tree_cell(root, pos)))
+        });
+        Pattern::with_cells(cells)
+    }
+
+    /// Sets the cells of `pattern` with its origin at `origin`. The cells in its bounding box
+    /// which the pattern doesn't list are left alone.
+    pub fn paste(&mut self, pattern: &Pattern, origin: impl Into<Position>) {
+        let origin = origin.into();
+        for &(offset, cell) in &pattern.cells {
+            self.set_cell(origin + offset, cell);
+        }
+    }
+
+    /// Finds every placement of `pattern` in one of the `orientations`, where the cells
+    /// around its bounding box are dead as well, so that the match is isolated.
+    ///
+    /// Each match is reported once with the position of the origin of the pattern and the
+    /// first of the `orientations` producing it, such that the cell at `offset` in the
+    /// pattern is at `position + transform.apply(offset)`. Matches are sorted by position.
+    ///
+    /// The tree is walked down to small blocks, skipping subtrees which are empty or don't
+    /// have enough cells within reach to hold the pattern, and only placements around the
+    /// cells of the remaining blocks are looked at. With an alive background, the pattern
+    /// describes the cells differing from it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is empty, as it would match everywhere.
+    pub fn find(
+        &self,
+        pattern: &Pattern,
+        orientations: &[Transform],
+    ) -> Vec<(Position, Transform)> {
+        let images = images(pattern, orientations);
+        let store = self.store.lock();
+        let root = self.root.unwrap();
+        let level = root.node(&*store).level();
+        let (min, side) = (level.min_pos(), level.side_len() as i64);
+        let cell_at = |pos: Position| {
+            let inside =
+                (min.x..min.x + side).contains(&pos.x) && (min.y..min.y + side).contains(&pos.y);
+            if inside {
+                store.get_tree_cell(root, pos)
+            } else {
+                Cell::DEAD
+            }
+        };
+        // a match around a cell lies within the bounding box and its border in every direction
+        let reach = images
+            .iter()
+            .map(|image| {
+                let (_, far) = image.pattern.bounds();
+                far.dx.max(far.dy) + 1
+            })
+            .max()
+            .unwrap();
+
+        let mut matches = Vec::new();
+        let mut stack = vec![(root, min)];
+        while let Some((tree, corner)) = stack.pop() {
+            let node = tree.node(&*store);
+            let side = node.level().side_len() as i64;
+            let far = corner + Offset::new(side - 1, side - 1);
+            let (near, beyond) = (
+                corner - Offset::new(reach, reach),
+                far + Offset::new(reach, reach),
+            );
+            if node.population() == 0
+                || store.population_in(root, min, near, beyond) < pattern.cells.len() as u32
+            {
+                continue;
+            }
+            match node {
+                Node::Inode(inode) if side > BLOCK_SIDE => {
+                    let half = side / 2;
+                    stack.push((inode.nw, corner));
+                    stack.push((inode.ne, corner + Offset::new(half, 0)));
+                    stack.push((inode.sw, corner + Offset::new(0, half)));
+                    stack.push((inode.se, corner + Offset::new(half, half)));
+                }
+                _ => store.for_each_alive(tree, corner, &mut |pos| {
+                    matches.extend(
+                        images
+                            .iter()
+                            .filter_map(|image| image.match_at(pos, &cell_at)),
+                    )
+                }),
+            }
+        }
+        sort_matches(&mut matches, orientations);
+        matches
+    }
+
+    // the cells which aren't dead by their position, as placements look at a few dozen cells
+    // each, which are cheaper to look up in a map than in the tree
+    pub(crate) fn cell_map(&self) -> HashMap<(i64, i64), Cell> {
+        let store = self.store.lock();
+        let root = self.root.unwrap();
+        let mut cells = HashMap::new();
+        store.for_each_alive(root, root.node(&*store).level().min_pos(), &mut |pos| {
+            cells.insert((pos.x, pos.y), store.get_tree_cell(root, pos));
+        });
+        cells
+    }
+}
+
+// side of the blocks `Universe::find` stops walking the tree at
+const BLOCK_SIDE: i64 = 16;
+
+// an orientation of a pattern to look for, moved to start at its north west corner
+struct Image {
+    pattern: Pattern,
+    // the north west corner of the oriented pattern, relative to its origin
+    shift: Offset,
+    transform: Transform,
+    // the dead cells in and around the bounding box
+    dead: Vec<Offset>,
+}
+
+impl Image {
+    // the match placing the first cell of the image at `pos`
+    fn match_at(
+        &self,
+        pos: Position,
+        cell_at: &impl Fn(Position) -> Cell,
+    ) -> Option<(Position, Transform)> {
+        let (anchor, anchor_cell) = self.pattern.cells[0];
+        if cell_at(pos) != anchor_cell {
+            return None;
+        }
+        let corner = pos - anchor;
+        let found = self
+            .pattern
+            .cells
+            .iter()
+            .all(|&(offset, cell)| cell_at(corner + offset) == cell)
+            && self
+                .dead
+                .iter()
+                .all(|&offset| cell_at(corner + offset) == Cell::DEAD);
+        found.then_some((corner - self.shift, self.transform))
+    }
+}
+
+// the `orientations` of `pattern`, skipping those which only reproduce an earlier one
+fn images(pattern: &Pattern, orientations: &[Transform]) -> Vec<Image> {
+    assert!(!pattern.is_empty(), "the empty pattern matches everywhere");
+    let mut images: Vec<Image> = Vec::new();
+    for &transform in orientations {
+        let (image, shift) = pattern.transformed(transform).normalized();
+        if images.iter().any(|other| other.pattern == image) {
+            continue;
+        }
+        let (_, far) = image.bounds();
+        let dead = (-1..=far.dy + 1)
+            .flat_map(|dy| (-1..=far.dx + 1).map(move |dx| Offset::new(dx, dy)))
+            .filter(|&offset| image.cells.binary_search_by_key(&offset, |c| c.0).is_err())
+            .collect();
+        images.push(Image {
+            pattern: image,
+            shift,
+            transform,
+            dead,
+        });
+    }
+    images
+}
+
+// sorts matches by position, and those at the same position by their orientation
+fn sort_matches(matches: &mut [(Position, Transform)], orientations: &[Transform]) {
+    matches
+        .sort_by_key(|&(pos, transform)| (pos, orientations.iter().position(|&t| t == transform)));
+}
+
+// the matches of `Universe::find` among `cells` which contain one of the `alive` cells
+pub(crate) fn find_around(
+    cells: &HashMap<(i64, i64), Cell>,
+    pattern: &Pattern,
+    orientations: &[Transform],
+    alive: &[Position],
+) -> Vec<(Position, Transform)> {
+    let cell_at = |pos: Position| cells.get(&(pos.x, pos.y)).copied().unwrap_or(Cell::DEAD);
+    let mut matches = Vec::new();
+    for image in images(pattern, orientations) {
+        matches.extend(
+            alive
+                .iter()
+                .filter_map(|&pos| image.match_at(pos, &cell_at)),
+        );
+    }
+    sort_matches(&mut matches, orientations);
+    matches
+}
@@ -0,0 +1,343 @@
+use crate::{
+    core::{Cell, Offset, Position},
+    pattern::{Pattern, Transform},
+    rule::Rule,
+    universe::Universe,
+};
+
+/// Searches for patterns which evolve into the cells of a region of a [`Universe`].
+///
+/// The parents are found by backtracking over the cells of a bounded region, where all cells
+/// outside of it are dead, row by row from the north west. Each cell of the target is checked
+/// as soon as the cells around it are set, so small regions are searched quickly, but the
+/// work grows exponentially with their size.
+#[derive(Debug, Clone)]
+pub struct PredecessorSearch {
+    // the next state for each `center << 8 | neighborhood`
+    next: Vec<bool>,
+    target: Vec<(Position, Cell)>,
+    target_min: Position,
+    target_max: Position,
+    region: Option<(Position, Position)>,
+    generations: usize,
+    max_population: Option<u32>,
+    symmetries: Vec<Transform>,
+}
+
+// the search for the parent cells of one generation, see `PredecessorSearch::layer`
+struct Layer<'a> {
+    next: &'a [bool],
+    min: Position,
+    width: i64,
+    height: i64,
+    // the cells each constraint looks at, and the state it needs to have next
+    constraints: Vec<([Option<usize>; 9], bool)>,
+    // the constraints which can be checked once the cell with their index is set
+    ready: Vec<Vec<usize>>,
+    // the cell with the smallest index each cell has to equal, if any
+    equal: Vec<Option<usize>>,
+    // the cells which have to stay dead, like those symmetric to cells outside of the region
+    dead: Vec<bool>,
+    max_population: Option<u32>,
+}
+
+impl PredecessorSearch {
+    /// Searches for parents of the cells from `min` to `max` one generation earlier.
+    ///
+    /// Unless [`PredecessorSearch::with_region`] sets another one, parents are searched in the
+    /// region one cell larger on every side for each generation, which contains all cells the
+    /// target depends on.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless the universe is infinite and runs a two-state rule with radius 1 which
+    /// is neither a Margolus rule nor one with `B0`.
+    pub fn new(universe: &Universe, min: impl Into<Position>, max: impl Into<Position>) -> Self {
+        let (min, max) = (min.into(), max.into());
+        assert!(
+            universe.grid.is_none(),
+            "predecessor search needs the infinite plane"
+        );
+        let rule = universe.rule();
+        assert!(
+            rule.states() == 2 && rule.radius() == 1 && !rule.is_margolus(),
+            "predecessor search needs a two-state rule on the Moore neighborhood"
+        );
+        assert!(
+            !rule.is_born(0),
+            "predecessor search needs a rule without B0"
+        );
+        let target = (min.y..=max.y)
+            .flat_map(|y| (min.x..=max.x).map(move |x| Position::new(x, y)))
+            .map(|pos| (pos, universe.get_cell(pos)))
+            .collect();
+        Self {
+            next: next_table(&rule),
+            target,
+            target_min: min,
+            target_max: max,
+            region: None,
+            generations: 1,
+            max_population: None,
+            symmetries: Vec::new(),
+        }
+    }
+
+    /// Searches for ancestors `generations` generations earlier. All generations in between
+    /// stay within the region as well.
+    pub fn with_generations(mut self, generations: usize) -> Self {
+        assert!(generations > 0, "a parent is at least a generation earlier");
+        self.generations = generations;
+        self
+    }
+
+    /// Only searches the parents from `min` to `max`, all cells outside of it are dead.
+    pub fn with_region(mut self, min: impl Into<Position>, max: impl Into<Position>) -> Self {
+        self.region = Some((min.into(), max.into()));
+        self
+    }
+
+    /// Only finds parents with at most `max_population` alive cells.
+    pub fn with_max_population(mut self, max_population: u32) -> Self {
+        self.max_population = Some(max_population);
+        self
+    }
+
+    /// Only finds parents which `transform` around the centre of the region maps onto
+    /// themselves. Cells mapped outside of the region are dead.
+    pub fn with_symmetry(mut self, transform: Transform) -> Self {
+        self.symmetries.push(transform);
+        self
+    }
+
+    /// The first parent found, if there is any.
+    pub fn find(&self) -> Option<Pattern> {
+        self.find_all(1).pop()
+    }
+
+    /// Up to `limit` different parents, with the cells at their positions as offsets from
+    /// the origin, see [`Universe::paste`].
+    pub fn find_all(&self, limit: usize) -> Vec<Pattern> {
+        let mut parents = Vec::new();
+        if limit > 0 {
+            self.search(&self.target, self.generations, limit, &mut parents);
+        }
+        parents
+    }
+
+    // searches for the parents of `target` `generations` generations earlier, and returns
+    // false once `limit` parents are found
+    fn search(
+        &self,
+        target: &[(Position, Cell)],
+        generations: usize,
+        limit: usize,
+        parents: &mut Vec<Pattern>,
+    ) -> bool {
+        let last = generations == 1;
+        let layer = self.layer(target, last);
+        let (min, max) = self.region();
+        let (min, max) = (min - Offset::new(1, 1), max + Offset::new(1, 1));
+        layer.search(&mut |cells: &[bool]| {
+            let mut parent = Vec::new();
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    let alive = layer.index(Position::new(x, y)).is_some_and(|i| cells[i]);
+                    parent.push((Position::new(x, y), Cell::new(alive as u8)));
+                }
+            }
+            if !last {
+                return self.search(&parent, generations - 1, limit, parents);
+            }
+            let pattern = Pattern::with_cells(
+                parent
+                    .into_iter()
+                    .map(|(pos, cell)| (Offset::new(pos.x, pos.y), cell)),
+            );
+            if !parents.contains(&pattern) {
+                parents.push(pattern);
+            }
+            parents.len() < limit
+        });
+        parents.len() < limit
+    }
+
+    fn region(&self) -> (Position, Position) {
+        self.region.unwrap_or_else(|| {
+            let grow = Offset::new(self.generations as i64, self.generations as i64);
+            (self.target_min - grow, self.target_max + grow)
+        })
+    }
+
+    // the search for parents of `target` within the region, with the constraints on the
+    // parents themselves if they are the `last` generation
+    fn layer(&self, target: &[(Position, Cell)], last: bool) -> Layer<'_> {
+        let (min, max) = self.region();
+        let (width, height) = ((max.x - min.x + 1).max(0), (max.y - min.y + 1).max(0));
+        let size = (width * height) as usize;
+        let mut layer = Layer {
+            next: &self.next,
+            min,
+            width,
+            height,
+            constraints: Vec::new(),
+            ready: vec![Vec::new(); size + 1],
+            equal: vec![None; size],
+            dead: vec![false; size],
+            max_population: if last { self.max_population } else { None },
+        };
+        for &(pos, cell) in target {
+            let mut around = [None; 9];
+            for (i, cell) in around.iter_mut().enumerate() {
+                let offset = Offset::new(i as i64 % 3 - 1, i as i64 / 3 - 1);
+                *cell = layer.index(pos + offset);
+            }
+            // checked after the last cell around it is set, or right away if there are none
+            let ready = around.iter().flatten().max().map_or(0, |&i| i + 1);
+            layer.ready[ready].push(layer.constraints.len());
+            layer.constraints.push((around, cell == Cell::ALIVE));
+        }
+
+        if last {
+            let double_center = Position::new(min.x + max.x, min.y + max.y);
+            for i in 0..size {
+                let pos = layer.position(i);
+                for &transform in &self.symmetries {
+                    let offset = transform.apply(Offset::new(
+                        2 * pos.x - double_center.x,
+                        2 * pos.y - double_center.y,
+                    ));
+                    let image = double_center + offset;
+                    // images between cells don't exist either
+                    let index = Some(image)
+                        .filter(|image| (image.x | image.y) & 1 == 0)
+                        .and_then(|image| layer.index(Position::new(image.x / 2, image.y / 2)));
+                    match index {
+                        Some(j) => layer.join(i, j),
+                        None => layer.dead[i] = true,
+                    }
+                }
+            }
+            // cells have to equal the first cell of their orbit, which is dead if any is
+            for i in 0..size {
+                let first = layer.first(i);
+                layer.dead[first] |= layer.dead[i];
+                layer.equal[i] = Some(first).filter(|&first| first != i);
+            }
+        }
+        layer
+    }
+}
+
+impl Layer<'_> {
+    fn index(&self, pos: Position) -> Option<usize> {
+        let (x, y) = (pos.x - self.min.x, pos.y - self.min.y);
+        if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+
+    fn position(&self, index: usize) -> Position {
+        let index = index as i64;
+        self.min + Offset::new(index % self.width, index / self.width)
+    }
+
+    // the first cell of the orbit of cell `i`, following `equal` like a union-find forest
+    fn first(&self, mut i: usize) -> usize {
+        while let Some(j) = self.equal[i] {
+            i = j;
+        }
+        i
+    }
+
+    fn join(&mut self, i: usize, j: usize) {
+        let (i, j) = (self.first(i), self.first(j));
+        if i != j {
+            self.equal[i.max(j)] = Some(i.min(j));
+        }
+    }
+
+    fn check(&self, cells: &[bool], index: usize) -> bool {
+        self.ready[index].iter().all(|&constraint| {
+            let (around, alive) = &self.constraints[constraint];
+            let state = around.iter().fold(0, |state, cell| {
+                state << 1 | cell.is_some_and(|i| cells[i]) as usize
+            });
+            // the center comes first in the table, the neighbors from the north west after it
+            let center = state >> 4 & 1;
+            let neighborhood = (state >> 5) << 4 | (state & 0xf);
+            self.next[center << 8 | neighborhood] == *alive
+        })
+    }
+
+    // calls `found` with each parent until it returns false
+    fn search(&self, found: &mut impl FnMut(&[bool]) -> bool) {
+        let size = self.equal.len();
+        if !self.check(&[], 0) {
+            return;
+        }
+        let mut cells = vec![false; size];
+        let mut population = 0;
+        self.search_from(0, &mut cells, &mut population, found);
+    }
+
+    // sets the cells from `index` on and returns false once the search is over
+    fn search_from(
+        &self,
+        index: usize,
+        cells: &mut [bool],
+        population: &mut u32,
+        found: &mut impl FnMut(&[bool]) -> bool,
+    ) -> bool {
+        if index == cells.len() {
+            return found(cells);
+        }
+        let choices: &[bool] = match self.equal[index] {
+            _ if self.dead[index] => &[false],
+            Some(first) if cells[first] => &[true],
+            Some(_) => &[false],
+            None => &[false, true],
+        };
+        for &alive in choices {
+            cells[index] = alive;
+            *population += alive as u32;
+            let allowed = self.max_population.is_none_or(|max| *population <= max);
+            let go_on = !allowed
+                || !self.check(cells, index + 1)
+                || self.search_from(index + 1, cells, population, found);
+            *population -= alive as u32;
+            cells[index] = false;
+            if !go_on {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// the next state of a cell for each `center << 8 | neighborhood`
+fn next_table(rule: &Rule) -> Vec<bool> {
+    (0..512)
+        .map(|index: usize| {
+            let mut neighbors = [Cell::DEAD; 8];
+            for (i, neighbor) in neighbors.iter_mut().enumerate() {
+                *neighbor = Cell::new((index >> (7 - i) & 1) as u8);
+            }
+            rule.next(Cell::new((index >> 8) as u8), &neighbors) == Cell::ALIVE
+        })
+        .collect()
+}
+
+impl Universe {
+    /// Whether no pattern evolves into the cells from `min` to `max`, which makes every
+    /// pattern containing them a Garden of Eden.
+    ///
+    /// # Panics
+    ///
+    /// Panics for bounded grids and rules [`PredecessorSearch`] doesn't support.
+    pub fn is_garden_of_eden(&self, min: impl Into<Position>, max: impl Into<Position>) -> bool {
+        PredecessorSearch::new(self, min, max).find().is_none()
+    }
+}
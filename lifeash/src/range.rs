@@ -0,0 +1,163 @@
+use std::fmt;
+
+use crate::{
+    core::{Cell, Position},
+    rule::Rule,
+    store::SharedStore,
+    table::{Limit, ReadTable, Table},
+    universe::{Id, Universe},
+};
+
+/// The rules [`Universe::rule_range`] looks at.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RuleFamily {
+    /// Outer totalistic rules like `B36/S23`, which only count neighbors.
+    LifeLike,
+    /// Isotropic non-totalistic rules like `B2-a/S12`, which tell apart arrangements of
+    /// neighbors up to rotations and reflections.
+    Isotropic,
+}
+
+/// All rules of a family between a minimal and a maximal rule, see
+/// [`Universe::rule_range`].
+///
+/// A rule is in the range if every cell the minimal rule turns alive turns alive in it as
+/// well, and every cell it turns alive turns alive in the maximal rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleRange {
+    pub min: Rule,
+    pub max: Rule,
+}
+
+impl RuleRange {
+    /// Whether `rule` is between the minimal and the maximal rule. Only two-state rules on
+    /// the Moore neighborhood can be.
+    pub fn contains(&self, rule: &Rule) -> bool {
+        match (self.min.table(), self.max.table(), rule.table()) {
+            (Some(min), Some(max), Some(table)) => {
+                (0..8).all(|i| min[i] & !table[i] == 0 && table[i] & !max[i] == 0)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for RuleRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} - {}", self.min, self.max)
+    }
+}
+
+// collects the nodes at the base level an evolution computes the results of
+#[derive(Default)]
+struct Recorder {
+    evaluated: Vec<(Id, Id)>,
+}
+
+impl Limit for Recorder {
+    fn proceed(&mut self) -> bool {
+        true
+    }
+
+    fn finished(&mut self, _depth: u32) {}
+
+    fn evaluated(&mut self, node: Id, result: Id) {
+        self.evaluated.push((node, result));
+    }
+}
+
+impl Universe {
+    /// The range of rules in `family` under which the pattern evolves the same way for
+    /// `generations` generations, or `None` if no rule of the family does.
+    ///
+    /// Evolves a copy of the universe in a store of its own, so that no result is taken
+    /// from earlier evolutions, and records the neighborhood of every cell the evolution
+    /// computes, together with its next state. The minimal rule turns only the recorded
+    /// neighborhoods with an alive outcome alive, the maximal one also all neighborhoods
+    /// which weren't recorded, each together with the neighborhoods the family can't tell
+    /// apart from it.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless the universe is infinite and runs a two-state isotropic rule on the
+    /// Moore neighborhood without `B0`.
+    pub fn rule_range(&self, generations: usize, family: RuleFamily) -> Option<RuleRange> {
+        assert!(self.grid.is_none(), "rule ranges need the infinite plane");
+        let rule = self.rule();
+        assert!(
+            rule.table().is_some(),
+            "rule ranges need a two-state isotropic rule on the Moore neighborhood"
+        );
+        assert!(!rule.is_born(0), "rule ranges need a rule without B0");
+
+        // the next state of the cells with each `center << 8 | neighborhood`, if any had it
+        let mut outcomes: Vec<Option<bool>> = vec![None; 512];
+        // empty space stays empty
+        outcomes[0] = Some(false);
+        let neighbors: Vec<(i64, i64)> = (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(|&offset| offset != (0, 0))
+            .collect();
+        let mut universe = Universe::with_store(&SharedStore::with_rule(rule.clone()));
+        universe.initialize();
+        universe.paste(&self.to_pattern(), Position::ORIGIN);
+        let mut recorder = Recorder::default();
+        for _ in 0..generations {
+            let mut store = universe.store.lock_for_evolution(universe.generation);
+            let root = store.expand_for_evolution(universe.root.unwrap());
+            let result = store.try_evolve_tree(root, 0, &mut recorder).unwrap();
+            universe.root = Some(store.shrink(result));
+            universe.generation += 1;
+        }
+
+        // the base level of the rule is 2, so each node holds 4 by 4 cells around its center
+        // and its result the 2 by 2 cells in the middle
+        let store = universe.store.lock();
+        for &(node, result) in &recorder.evaluated {
+            for (x, y) in [(-1, -1), (0, -1), (-1, 0), (0, 0)] {
+                let alive = |dx: i64, dy: i64| {
+                    store.get_tree_cell(node, Position::new(x + dx, y + dy)) == Cell::ALIVE
+                };
+                let neighborhood = neighbors.iter().fold(0, |neighborhood, &(dx, dy)| {
+                    neighborhood << 1 | alive(dx, dy) as usize
+                });
+                let next = store.get_tree_cell(result, Position::new(x, y)) == Cell::ALIVE;
+                outcomes[(alive(0, 0) as usize) << 8 | neighborhood] = Some(next);
+            }
+        }
+
+        let (mut min, mut max) = ([0u64; 8], [0u64; 8]);
+        let mut done = [false; 512];
+        for index in 0..512 {
+            if done[index] {
+                continue;
+            }
+            let (center, neighborhood) = (index >> 8, index as u8);
+            let class: Vec<usize> =
+                Rule::equivalent_neighborhoods(neighborhood, family == RuleFamily::LifeLike)
+                    .into_iter()
+                    .map(|n| center << 8 | n as usize)
+                    .collect();
+            let mut seen = class.iter().filter_map(|&i| outcomes[i]);
+            let outcome = seen.next();
+            if let Some(outcome) = outcome {
+                if seen.any(|other| other != outcome) {
+                    return None;
+                }
+            }
+            for &i in &class {
+                done[i] = true;
+                if outcome == Some(true) {
+                    min[i / 64] |= 1 << (i % 64);
+                }
+                if outcome != Some(false) {
+                    max[i / 64] |= 1 << (i % 64);
+                }
+            }
+        }
+        Some(RuleRange {
+            min: Rule::from_table(min),
+            max: Rule::from_table(max),
+        })
+    }
+}
@@ -0,0 +1,944 @@
+use std::{convert::TryInto, error::Error, fmt, ops::RangeInclusive, str::FromStr, sync::Arc};
+
+use crate::{
+    core::{Cell, Offset, Position},
+    rule_file::Transitions,
+};
+
+/// Rule on the Moore neighborhood or a subset of it.
+///
+/// Most rules are isotropic rules on a [`Neighborhood`], optionally with decaying states.
+/// With two states this is a Life-like rule such as `B3/S23`, or an isotropic non-totalistic
+/// one such as `B2-a/S12`, where Hensel's letters after a neighbor count select which
+/// arrangements of that many neighbors apply. With more states it is a Generations rule such
+/// as `B2/S/C3`: an alive cell that doesn't survive passes through the refractory states
+/// `2..states` before it dies, and only alive cells count as neighbors.
+///
+/// Rules on the von Neumann or the hexagonal neighborhood carry a `V` or `H` suffix, like
+/// `B2/S34H`. Hexagonal rules can use the letters `o`, `m` and `p` for ortho, meta and para
+/// arrangements of two to four neighbors, where those of four neighbors name the two dead ones.
+///
+/// Larger than Life rules such as `R5,C0,M1,S34..58,B34..45,NM` count the alive cells within
+/// a radius of up to 500 cells on the Moore (`NM`), von Neumann (`NN`) or hexagonal (`NH`)
+/// neighborhood, including the cell itself with `M1`. Birth and survival need counts in the
+/// given ranges, and `C` states more than two work like those of Generations rules.
+///
+/// Margolus rules such as `MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15`, the billiard-ball
+/// machine, don't look at neighbors but partition the plane into blocks of 2x2 cells, which
+/// start at even coordinates in even generations and at odd ones in odd generations. Each
+/// block turns into the block at its index in a table of 16 entries, where the north west,
+/// north east, south west and south east cells are worth 1, 2, 4 and 8.
+///
+/// Rules with `B0` turn the empty background alive, see [`Rule::background`]. Margolus rules
+/// can do the same by turning the empty block into the full one.
+///
+/// Any other rule can be loaded from a Golly rule file with [`RuleFile`](crate::RuleFile).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Rule {
+    kind: Kind,
+    states: u8,
+    neighborhood: Neighborhood,
+}
+
+/// The neighbors an isotropic [`Rule`] looks at.
+///
+/// The hexagonal neighborhood is emulated on the square grid the way Golly does it: the
+/// north east and south west neighbors are ignored, which is a hexagonal grid sheared
+/// to the right going up.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Neighborhood {
+    /// All eight surrounding cells.
+    Moore,
+    /// The four orthogonally adjacent cells.
+    VonNeumann,
+    /// Six of the surrounding cells, leaving out the north east and south west ones.
+    Hexagonal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Kind {
+    // bit `center << 8 | neighborhood` is set if an alive (`center == 1`) or dead cell with
+    // that neighborhood is alive in the next generation
+    Isotropic([u64; 8]),
+    // the neighbors' states matter, not only whether they are alive
+    File(Arc<Transitions>),
+    // Larger than Life, counting the alive cells within `radius` in the rule's neighborhood
+    LargerThanLife {
+        radius: u32,
+        middle: bool,
+        survival: RangeInclusive<u32>,
+        birth: RangeInclusive<u32>,
+    },
+    // the next block for each block of 2x2 cells, whose partition starts at odd coordinates
+    // in the generations this rule runs in if `odd` is set
+    Margolus {
+        table: [u8; 16],
+        odd: bool,
+    },
+}
+
+// the largest radius of Larger than Life rules, as in Golly
+const MAX_RADIUS: u32 = 500;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRuleError {
+    rule: String,
+    reason: &'static str,
+}
+
+// the bits of the neighbors in a neighborhood, row by row from the north west
+const NW: u8 = 1 << 7;
+const N: u8 = 1 << 6;
+const NE: u8 = 1 << 5;
+const W: u8 = 1 << 4;
+const E: u8 = 1 << 3;
+const SW: u8 = 1 << 2;
+const S: u8 = 1 << 1;
+const SE: u8 = 1;
+
+// the neighbors clockwise around the cell
+const RING: [u8; 8] = [N, NE, E, SE, S, SW, W, NW];
+const VON_NEUMANN_RING: [u8; 4] = [N, E, S, W];
+const HEXAGONAL_RING: [u8; 6] = [N, E, SE, S, W, NW];
+
+// Hensel's letters in canonical order with a representative of their neighborhoods,
+// for one to four neighbors. Five to seven neighbors use the complements of three to one.
+const LETTERS: [&[(char, u8)]; 5] = [
+    &[],
+    &[('c', NE), ('e', N)],
+    &[
+        ('c', NE | SE),
+        ('e', N | E),
+        ('k', N | SE),
+        ('a', N | NE),
+        ('i', N | S),
+        ('n', NE | SW),
+    ],
+    &[
+        ('c', NE | SE | SW),
+        ('e', N | E | S),
+        ('k', N | E | SW),
+        ('a', N | NE | E),
+        ('i', N | NE | NW),
+        ('n', N | NE | SE),
+        ('y', N | SE | SW),
+        ('q', N | NE | SW),
+        ('j', N | NE | W),
+        ('r', N | NE | S),
+    ],
+    &[
+        ('c', NE | SE | SW | NW),
+        ('e', N | E | S | W),
+        ('k', N | NE | SE | W),
+        ('a', N | NE | E | SE),
+        ('i', N | NE | SE | S),
+        ('n', N | NE | SE | NW),
+        ('y', N | NE | SE | SW),
+        ('q', N | NE | E | SW),
+        ('j', N | NE | S | W),
+        ('r', N | NE | E | S),
+        ('t', N | NE | S | NW),
+        ('w', N | NE | SW | W),
+        ('z', N | NE | S | SW),
+    ],
+];
+
+// the letters for ortho, meta and para arrangements of hexagonal neighbors, for one to three
+// neighbors. four neighbors use the complements of two.
+const HEXAGONAL_LETTERS: [&[(char, u8)]; 4] = [
+    &[],
+    &[],
+    &[('o', N | E), ('m', N | SE), ('p', N | S)],
+    &[('o', N | E | SE), ('m', N | E | S), ('p', N | SE | W)],
+];
+
+impl Neighborhood {
+    // the bits of the neighbors in a neighborhood
+    fn mask(self) -> u8 {
+        self.ring()
+            .iter()
+            .fold(0, |mask, &neighbor| mask | neighbor)
+    }
+
+    fn ring(self) -> &'static [u8] {
+        match self {
+            Neighborhood::Moore => &RING,
+            Neighborhood::VonNeumann => &VON_NEUMANN_RING,
+            Neighborhood::Hexagonal => &HEXAGONAL_RING,
+        }
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            Neighborhood::Moore => "",
+            Neighborhood::VonNeumann => "V",
+            Neighborhood::Hexagonal => "H",
+        }
+    }
+
+    // the letters for `count` neighbors with a representative of their neighborhoods
+    fn letters(self, count: u32) -> impl Iterator<Item = (char, u8)> {
+        let table: &[&[(char, u8)]] = match self {
+            Neighborhood::Moore => &LETTERS,
+            Neighborhood::VonNeumann => &[],
+            Neighborhood::Hexagonal => &HEXAGONAL_LETTERS,
+        };
+        let (count, size) = (count as usize, self.ring().len());
+        let (letters, complement) = if count < table.len() {
+            (table[count], 0)
+        } else if size - count < table.len() {
+            (table[size - count], self.mask())
+        } else {
+            (&[][..], 0)
+        };
+        letters
+            .iter()
+            .map(move |&(letter, neighborhood)| (letter, neighborhood ^ complement))
+    }
+
+    // all rotations and reflections of `neighborhood`
+    fn symmetries(self, neighborhood: u8) -> impl Iterator<Item = u8> {
+        let ring = self.ring();
+        let len = ring.len();
+        // rotating the Moore neighborhood by a single neighbor would turn the square
+        let step = if self == Neighborhood::Moore { 2 } else { 1 };
+        let transform = move |rotation: usize, reflect: bool| {
+            let mut result = 0;
+            for (i, &neighbor) in ring.iter().enumerate() {
+                if neighborhood & neighbor != 0 {
+                    let j = if reflect { len - i } else { i };
+                    result |= ring[(j + step * rotation) % len];
+                }
+            }
+            result
+        };
+        (0..len / step)
+            .flat_map(move |rotation| vec![transform(rotation, false), transform(rotation, true)])
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::life()
+    }
+}
+
+impl Rule {
+    /// Conway's Game of Life, `B3/S23`.
+    pub fn life() -> Self {
+        Self::new(&[3], &[2, 3], 2)
+    }
+
+    /// Creates an outer totalistic rule from the neighbor counts leading to a birth or
+    /// survival and the number of states, where two states make a Life-like rule.
+    pub fn new(birth: &[u8], survival: &[u8], states: u8) -> Self {
+        Self::with_neighborhood(Neighborhood::Moore, birth, survival, states)
+    }
+
+    /// Creates an outer totalistic rule like [`Rule::new`], counting only the neighbors
+    /// in `neighborhood`.
+    pub fn with_neighborhood(
+        neighborhood: Neighborhood,
+        birth: &[u8],
+        survival: &[u8],
+        states: u8,
+    ) -> Self {
+        assert!(states >= 2, "a rule needs at least two states");
+        assert!(
+            states == 2 || !birth.contains(&0),
+            "B0 is only supported with two states"
+        );
+        let mut rule = Self {
+            kind: Kind::Isotropic([0; 8]),
+            states,
+            neighborhood,
+        };
+        let mask = neighborhood.mask();
+        for (center, counts) in [(0, birth), (1, survival)].iter() {
+            for &count in counts.iter() {
+                assert!(
+                    usize::from(count) <= neighborhood.ring().len(),
+                    "too many neighbors for the neighborhood"
+                );
+                for neighborhood in 0..=255u8 {
+                    if (neighborhood & mask).count_ones() == u32::from(count) {
+                        rule.set(*center, neighborhood);
+                    }
+                }
+            }
+        }
+        rule
+    }
+
+    /// Creates the Margolus rule turning each block into the block at its index in `table`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an entry isn't a block, or if the empty block turns into any block but the
+    /// empty or the full one. If it turns into the full block, the full block has to turn into
+    /// either of them as well, so that the background stays uniform.
+    pub fn margolus(table: [u8; 16]) -> Self {
+        assert!(table.iter().all(|&block| block < 16), "blocks are 4 bits");
+        assert!(
+            table[0] == 0 || (table[0] == 15 && (table[15] == 0 || table[15] == 15)),
+            "the background must stay uniform"
+        );
+        Self {
+            kind: Kind::Margolus { table, odd: false },
+            states: 2,
+            neighborhood: Neighborhood::Moore,
+        }
+    }
+
+    pub(crate) fn from_file(transitions: Transitions, states: u8) -> Self {
+        Self {
+            kind: Kind::File(Arc::new(transitions)),
+            states,
+            neighborhood: Neighborhood::Moore,
+        }
+    }
+
+    pub fn states(&self) -> u8 {
+        self.states
+    }
+
+    /// The neighbors the rule looks at, within [`Rule::radius`]. Rule files always report
+    /// the Moore neighborhood, which contains all others.
+    pub fn neighborhood(&self) -> Neighborhood {
+        self.neighborhood
+    }
+
+    /// How far away cells can be to count as neighbors, which is more than 1 only for
+    /// Larger than Life rules.
+    pub fn radius(&self) -> u32 {
+        match self.kind {
+            Kind::LargerThanLife { radius, .. } => radius,
+            _ => 1,
+        }
+    }
+
+    /// Whether a dead cell with the alive neighbors in `neighborhood` is born.
+    ///
+    /// `neighborhood` has a bit for each alive neighbor, row by row from the most significant
+    /// bit for the north west neighbor to the least significant one for the south east neighbor.
+    /// The bits of neighbors outside of [`Rule::neighborhood`] don't matter.
+    pub fn is_born(&self, neighborhood: u8) -> bool {
+        self.next(Cell::DEAD, &alive_neighbors(neighborhood)) == Cell::ALIVE
+    }
+
+    /// Whether an alive cell with the alive neighbors in `neighborhood` survives.
+    pub fn survives(&self, neighborhood: u8) -> bool {
+        self.next(Cell::ALIVE, &alive_neighbors(neighborhood)) == Cell::ALIVE
+    }
+
+    /// State of the infinite empty background after `generation` generations.
+    ///
+    /// The background is dead in the first generation. With `B0` it is alive in the next one
+    /// and then stays alive with `S8`, or else keeps alternating between dead and alive.
+    pub fn background(&self, generation: usize) -> Cell {
+        let uniform = |cell| self.next_at(Position::ORIGIN, cell, |_| cell);
+        if generation == 0 || uniform(Cell::DEAD) == Cell::DEAD {
+            Cell::DEAD
+        } else if uniform(Cell::ALIVE) == Cell::ALIVE || generation % 2 == 1 {
+            Cell::ALIVE
+        } else {
+            Cell::DEAD
+        }
+    }
+
+    // the rule evolving the difference of the cells to the `background`, which is dead in the
+    // emulated rule. this is how rules with `B0` run without an infinite number of alive cells.
+    pub(crate) fn relative_to(&self, background: Cell) -> Self {
+        let table = match &self.kind {
+            Kind::Isotropic(_) if self.is_born(0) => [0; 8],
+            Kind::Margolus { table, odd } => {
+                let flip = match background {
+                    Cell::DEAD => 0,
+                    _ => 15,
+                };
+                let mut relative = [0; 16];
+                for (block, next) in relative.iter_mut().enumerate() {
+                    *next = table[block ^ flip] ^ table[flip];
+                }
+                return Self {
+                    kind: Kind::Margolus {
+                        table: relative,
+                        odd: *odd,
+                    },
+                    ..self.clone()
+                };
+            }
+            _ => return self.clone(),
+        };
+        // all neighbors are in the background state as well
+        let (flip, flip_neighbors) = match background {
+            Cell::DEAD => (0, 0),
+            _ => (1, 0xff),
+        };
+        let next_background = self.get(flip, flip_neighbors);
+        let mut rule = Self {
+            kind: Kind::Isotropic(table),
+            states: self.states,
+            neighborhood: self.neighborhood,
+        };
+        for center in 0..2 {
+            for neighborhood in 0..=255u8 {
+                if self.get(center ^ flip, neighborhood ^ flip_neighbors) != next_background {
+                    rule.set(center, neighborhood);
+                }
+            }
+        }
+        rule
+    }
+
+    // the rule as it runs in `generation`, which only differs for Margolus rules
+    pub(crate) fn in_generation(&self, generation: usize) -> Self {
+        match &self.kind {
+            Kind::Margolus { table, .. } => Self {
+                kind: Kind::Margolus {
+                    table: *table,
+                    odd: generation % 2 == 1,
+                },
+                ..self.clone()
+            },
+            _ => self.clone(),
+        }
+    }
+
+    // the table of a two-state isotropic rule on the Moore neighborhood, which has a bit
+    // `center << 8 | neighborhood` for each cell alive in the next generation
+    pub(crate) fn table(&self) -> Option<[u64; 8]> {
+        match self.kind {
+            Kind::Isotropic(table)
+                if self.states == 2 && self.neighborhood == Neighborhood::Moore =>
+            {
+                Some(table)
+            }
+            _ => None,
+        }
+    }
+
+    // the table of a two-state isotropic rule on any of the neighborhoods, where neighbors
+    // outside of it don't change the bit, which the base case of the evolution indexes
+    pub(crate) fn two_state_table(&self) -> Option<[u64; 8]> {
+        match self.kind {
+            Kind::Isotropic(table) if self.states == 2 => Some(table),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn from_table(table: [u64; 8]) -> Self {
+        Self {
+            kind: Kind::Isotropic(table),
+            states: 2,
+            neighborhood: Neighborhood::Moore,
+        }
+    }
+
+    // the Moore neighborhoods an outer totalistic rule can't tell apart from `neighborhood`,
+    // or with `totalistic` unset an isotropic one: its rotations and reflections
+    pub(crate) fn equivalent_neighborhoods(neighborhood: u8, totalistic: bool) -> Vec<u8> {
+        if totalistic {
+            (0..=255u8)
+                .filter(|n| n.count_ones() == neighborhood.count_ones())
+                .collect()
+        } else {
+            let mut neighborhoods: Vec<u8> = Neighborhood::Moore.symmetries(neighborhood).collect();
+            neighborhoods.sort_unstable();
+            neighborhoods.dedup();
+            neighborhoods
+        }
+    }
+
+    pub(crate) fn is_margolus(&self) -> bool {
+        matches!(self.kind, Kind::Margolus { .. })
+    }
+
+    pub(crate) fn is_larger_than_life(&self) -> bool {
+        matches!(self.kind, Kind::LargerThanLife { .. })
+    }
+
+    // the phase of `generation`, which selects the rule emulating it: bit 0 is the background
+    // and bit 1 is set for the odd generations of Margolus rules
+    pub(crate) fn phase(&self, generation: usize) -> usize {
+        let odd = self.is_margolus() && generation % 2 == 1;
+        self.background(generation).state() as usize | (odd as usize) << 1
+    }
+
+    /// The state a cell in state `cell` with the given `neighbors`, row by row from the
+    /// north west neighbor to the south east one, has next.
+    ///
+    /// Larger than Life rules see all cells further away as dead, see [`Rule::next_around`].
+    ///
+    /// # Panics
+    ///
+    /// Panics for Margolus rules, where the next state depends on the position of the cell
+    /// within its block.
+    pub fn next(&self, cell: Cell, neighbors: &[Cell; 8]) -> Cell {
+        let neighborhood = neighbors.iter().fold(0, |neighborhood, &neighbor| {
+            neighborhood << 1 | (neighbor == Cell::ALIVE) as u8
+        });
+        match &self.kind {
+            Kind::Isotropic(_) => {
+                let applies = cell.state() < 2 && self.get(cell.state().into(), neighborhood);
+                self.decay(cell, applies)
+            }
+            Kind::File(transitions) => transitions.next(cell, neighbors),
+            Kind::LargerThanLife { .. } => self.next_around(cell, |offset| {
+                let i = (offset.dy + 1) * 3 + offset.dx + 1;
+                let i = if i > 4 { i - 1 } else { i };
+                match (offset.dx.abs(), offset.dy.abs()) {
+                    (0, 0) => cell,
+                    (0..=1, 0..=1) => neighbors[i as usize],
+                    _ => Cell::DEAD,
+                }
+            }),
+            Kind::Margolus { .. } => panic!("Margolus rules depend on the position of the cell"),
+        }
+    }
+
+    // the state a cell in state `cell` at `pos` has next. only Margolus rules look at the
+    // position, or rather at its parity.
+    pub(crate) fn next_at(
+        &self,
+        pos: Position,
+        cell: Cell,
+        neighbor: impl Fn(Offset) -> Cell,
+    ) -> Cell {
+        let (table, odd) = match &self.kind {
+            Kind::Margolus { table, odd } => (table, *odd as i64),
+            _ => return self.next_around(cell, neighbor),
+        };
+        // the corner of the block the cell is in
+        let (x, y) = ((pos.x - odd).rem_euclid(2), (pos.y - odd).rem_euclid(2));
+        let mut block = 0;
+        for bit in 0..4 {
+            let (dx, dy) = (bit & 1, bit >> 1);
+            let corner = if (dx, dy) == (x, y) {
+                cell
+            } else {
+                neighbor(Offset::new(dx - x, dy - y))
+            };
+            block |= ((corner == Cell::ALIVE) as usize) << bit;
+        }
+        Cell::new(table[block] >> (y * 2 + x) & 1)
+    }
+
+    /// The state a cell in state `cell` has next, where `neighbor` gives the state of the cell
+    /// at an offset within [`Rule::radius`] from it.
+    ///
+    /// # Panics
+    ///
+    /// Panics for Margolus rules, like [`Rule::next`].
+    pub fn next_around(&self, cell: Cell, neighbor: impl Fn(Offset) -> Cell) -> Cell {
+        let radius = match &self.kind {
+            Kind::LargerThanLife { radius, .. } => i64::from(*radius),
+            Kind::Margolus { .. } => panic!("Margolus rules depend on the position of the cell"),
+            _ => {
+                let mut neighbors = [Cell::DEAD; 8];
+                let offsets = (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| Offset::new(dx, dy)));
+                let offsets = offsets.filter(|&offset| offset != Offset::new(0, 0));
+                for (neighbor_cell, offset) in neighbors.iter_mut().zip(offsets) {
+                    *neighbor_cell = neighbor(offset);
+                }
+                return self.next(cell, &neighbors);
+            }
+        };
+
+        let mut count = 0;
+        for dy in -radius..=radius {
+            for dx in self.reach(dy) {
+                if (dx, dy) != (0, 0) && neighbor(Offset::new(dx, dy)) == Cell::ALIVE {
+                    count += 1;
+                }
+            }
+        }
+        self.next_with_count(cell, count)
+    }
+
+    // the offsets `dx` of the row `dy` of the neighborhood of a Larger than Life rule,
+    // the cell itself included
+    pub(crate) fn reach(&self, dy: i64) -> RangeInclusive<i64> {
+        let radius = i64::from(self.radius());
+        match self.neighborhood {
+            Neighborhood::Moore => -radius..=radius,
+            Neighborhood::VonNeumann => -(radius - dy.abs())..=radius - dy.abs(),
+            Neighborhood::Hexagonal => (dy - radius).max(-radius)..=(dy + radius).min(radius),
+        }
+    }
+
+    // the next state of `cell` under a Larger than Life rule, with `count` alive cells in its
+    // neighborhood besides itself
+    pub(crate) fn next_with_count(&self, cell: Cell, count: u32) -> Cell {
+        let (middle, survival, birth) = match &self.kind {
+            Kind::LargerThanLife {
+                middle,
+                survival,
+                birth,
+                ..
+            } => (*middle, survival, birth),
+            _ => unreachable!("only Larger than Life rules count their neighbors"),
+        };
+        let count = count + (middle && cell == Cell::ALIVE) as u32;
+        let applies = match cell.state() {
+            0 => birth.contains(&count),
+            _ => survival.contains(&count),
+        };
+        self.decay(cell, applies)
+    }
+
+    // the next state of `cell`, given whether a dead cell is born or an alive one survives.
+    // other cells pass through the refractory states of Generations rules.
+    fn decay(&self, cell: Cell, applies: bool) -> Cell {
+        match cell.state() {
+            0 | 1 if applies => Cell::ALIVE,
+            0 => Cell::DEAD,
+            state if state + 1 < self.states => Cell::new(state + 1),
+            _ => Cell::DEAD,
+        }
+    }
+
+    fn get(&self, center: usize, neighborhood: u8) -> bool {
+        let index = center << 8 | neighborhood as usize;
+        match &self.kind {
+            Kind::Isotropic(table) => table[index / 64] >> (index % 64) & 1 == 1,
+            _ => unreachable!("only isotropic rules have a neighborhood table"),
+        }
+    }
+
+    fn set(&mut self, center: usize, neighborhood: u8) {
+        let index = center << 8 | neighborhood as usize;
+        match &mut self.kind {
+            Kind::Isotropic(table) => table[index / 64] |= 1 << (index % 64),
+            _ => unreachable!("only isotropic rules have a neighborhood table"),
+        }
+    }
+
+    // the neighborhoods of one half of the table in Hensel notation, using the shorter
+    // of listing the letters which apply and the ones which don't
+    fn neighborhoods(&self, center: usize) -> String {
+        let mask = self.neighborhood.mask();
+        let mut result = String::new();
+        for count in 0..=self.neighborhood.ring().len() as u32 {
+            let (applying, other): (Vec<_>, Vec<_>) = self
+                .neighborhood
+                .letters(count)
+                .partition(|&(_, neighborhood)| self.get(center, neighborhood));
+            if applying.is_empty() && other.is_empty() {
+                // without letters, any neighborhood with that many neighbors will do
+                let neighborhood = (0..=mask)
+                    .find(|&n| n & !mask == 0 && n.count_ones() == count)
+                    .unwrap();
+                if self.get(center, neighborhood) {
+                    result.push_str(&count.to_string());
+                }
+                continue;
+            }
+            if applying.is_empty() {
+                continue;
+            }
+            result.push_str(&count.to_string());
+            if other.is_empty() {
+                continue;
+            }
+            if other.len() < applying.len() {
+                result.push('-');
+                result.extend(other.iter().map(|&(letter, _)| letter));
+            } else {
+                result.extend(applying.iter().map(|&(letter, _)| letter));
+            }
+        }
+        result
+    }
+
+    // sets the neighborhoods of one half of the table from Hensel notation
+    fn parse_neighborhoods(
+        &mut self,
+        rule: &str,
+        center: usize,
+        neighborhoods: &str,
+    ) -> Result<(), ParseRuleError> {
+        let (mask, size) = (self.neighborhood.mask(), self.neighborhood.ring().len());
+        let mut chars = neighborhoods.chars().peekable();
+        while let Some(c) = chars.next() {
+            let count = match c.to_digit(10) {
+                Some(count) if count <= 8 => count,
+                _ => {
+                    return Err(ParseRuleError::new(
+                        rule,
+                        "neighbor counts must be digits from 0 to 8",
+                    ))
+                }
+            };
+            if count as usize > size {
+                return Err(ParseRuleError::new(
+                    rule,
+                    "too many neighbors for the neighborhood",
+                ));
+            }
+            let negated = chars.peek() == Some(&'-');
+            if negated {
+                chars.next();
+            }
+            let mut selected = Vec::new();
+            while let Some(letter) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                let letter = letter.to_ascii_lowercase();
+                match self
+                    .neighborhood
+                    .letters(count)
+                    .find(|&(known, _)| known == letter)
+                {
+                    Some((_, neighborhood)) => selected.push(neighborhood),
+                    None => {
+                        return Err(ParseRuleError::new(
+                            rule,
+                            "unknown neighborhood letter for the neighbor count",
+                        ))
+                    }
+                }
+                chars.next();
+            }
+            if negated && selected.is_empty() {
+                return Err(ParseRuleError::new(rule, "expected letters after `-`"));
+            }
+
+            // the arrangements of the neighbors in the neighborhood which are selected
+            let selected: Vec<u8> = selected
+                .into_iter()
+                .flat_map(|neighborhood| self.neighborhood.symmetries(neighborhood))
+                .collect();
+            let applies = |neighborhood: u8| {
+                let neighbors = neighborhood & mask;
+                neighbors.count_ones() == count
+                    && (selected.is_empty() || selected.contains(&neighbors) != negated)
+            };
+            for neighborhood in (0..=255u8).filter(|&n| applies(n)) {
+                self.set(center, neighborhood);
+            }
+        }
+        Ok(())
+    }
+}
+
+// strips one of the (upper case) `prefixes` from `part`, ignoring its case
+pub(crate) fn prefixed<'a>(part: &'a str, prefixes: &[char]) -> Option<&'a str> {
+    let mut chars = part.chars();
+    match chars.next() {
+        Some(c) if prefixes.contains(&c.to_ascii_uppercase()) => Some(chars.as_str()),
+        _ => None,
+    }
+}
+
+// the neighbors which are set in `neighborhood`, in the layout of `Rule::is_born`
+fn alive_neighbors(neighborhood: u8) -> [Cell; 8] {
+    let mut neighbors = [Cell::DEAD; 8];
+    for (i, neighbor) in neighbors.iter_mut().enumerate() {
+        if neighborhood << i & 0x80 != 0 {
+            *neighbor = Cell::ALIVE;
+        }
+    }
+    neighbors
+}
+
+/// Writes isotropic rules in canonical Hensel notation and rule files by their name.
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            Kind::File(transitions) => return write!(f, "{}", transitions.name),
+            Kind::LargerThanLife {
+                radius,
+                middle,
+                survival,
+                birth,
+            } => {
+                let states = if self.states == 2 { 0 } else { self.states };
+                let neighborhood = match self.neighborhood {
+                    Neighborhood::Moore => 'M',
+                    Neighborhood::VonNeumann => 'N',
+                    Neighborhood::Hexagonal => 'H',
+                };
+                return write!(
+                    f,
+                    "R{},C{},M{},S{}..{},B{}..{},N{}",
+                    radius,
+                    states,
+                    *middle as u8,
+                    survival.start(),
+                    survival.end(),
+                    birth.start(),
+                    birth.end(),
+                    neighborhood
+                );
+            }
+            Kind::Margolus { table, .. } => {
+                let blocks: Vec<String> = table.iter().map(|block| block.to_string()).collect();
+                return write!(f, "MS,D{}", blocks.join(";"));
+            }
+            Kind::Isotropic(_) => {}
+        }
+        write!(f, "B{}/S{}", self.neighborhoods(0), self.neighborhoods(1))?;
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        write!(f, "{}", self.neighborhood.suffix())
+    }
+}
+
+/// Parses `B3/S23`, `B2-a/S12`, `B2/S/C3` and `B2o/S34H` as well as the older `23/3` and
+/// `345/2/4` notations, which list the survival counts first, Larger than Life rules
+/// like `R5,C0,M1,S34..58,B34..45,NM` and Margolus rules in MCell's `MS,D...` notation.
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        if let Some(table) = prefixed(rule.trim(), &['M'])
+            .and_then(|rule| prefixed(rule, &['S']))
+            .and_then(|rule| rule.strip_prefix(','))
+            .and_then(|rule| prefixed(rule, &['D']))
+        {
+            return parse_margolus(rule, table);
+        }
+        if rule.contains(',') {
+            return parse_larger_than_life(rule);
+        }
+        let (trimmed, neighborhood) = match rule.trim() {
+            rule if rule.ends_with(&['H', 'h'][..]) => {
+                (&rule[..rule.len() - 1], Neighborhood::Hexagonal)
+            }
+            rule if rule.ends_with(&['V', 'v'][..]) => {
+                (&rule[..rule.len() - 1], Neighborhood::VonNeumann)
+            }
+            rule => (rule, Neighborhood::Moore),
+        };
+        let parts: Vec<&str> = trimmed.split('/').collect();
+        if !(2..=3).contains(&parts.len()) {
+            return Err(ParseRuleError::new(rule, "expected two or three parts"));
+        }
+
+        let (birth, survival) = match (prefixed(parts[0], &['B']), prefixed(parts[1], &['S'])) {
+            (Some(birth), Some(survival)) => (birth, survival),
+            (None, None) => (parts[1], parts[0]),
+            _ => {
+                return Err(ParseRuleError::new(
+                    rule,
+                    "expected B.../S... or S/B notation",
+                ))
+            }
+        };
+
+        let states = match parts.get(2) {
+            None => 2,
+            Some(states) => prefixed(states, &['C', 'G'])
+                .unwrap_or(states)
+                .parse()
+                .ok()
+                .filter(|&states| states >= 2)
+                .ok_or_else(|| ParseRuleError::new(rule, "expected a state count of at least 2"))?,
+        };
+
+        if states > 2 && birth.contains('0') {
+            return Err(ParseRuleError::new(
+                rule,
+                "B0 is only supported with two states",
+            ));
+        }
+
+        let mut result = Self::with_neighborhood(neighborhood, &[], &[], states);
+        result.parse_neighborhoods(rule, 0, birth)?;
+        result.parse_neighborhoods(rule, 1, survival)?;
+        Ok(result)
+    }
+}
+
+// parses Golly's notation of Larger than Life rules
+fn parse_larger_than_life(rule: &str) -> Result<Rule, ParseRuleError> {
+    let parts: Vec<&str> = rule.trim().split(',').collect();
+    if parts.len() != 6 {
+        return Err(ParseRuleError::new(
+            rule,
+            "expected R, C, M, S, B and N parts",
+        ));
+    }
+    let number = |part: &str, prefix| prefixed(part, &[prefix])?.parse::<u32>().ok();
+    let range = |part: &str, prefix| {
+        let (min, max) = prefixed(part, &[prefix])?.split_once("..")?;
+        Some(min.parse::<u32>().ok()?..=max.parse::<u32>().ok()?)
+    };
+
+    let radius = number(parts[0], 'R')
+        .filter(|radius| (1..=MAX_RADIUS).contains(radius))
+        .ok_or_else(|| ParseRuleError::new(rule, "expected a radius from 1 to 500"))?;
+    // `C0` and `C1` are the same as `C2`
+    let states = number(parts[1], 'C')
+        .filter(|&states| states <= u32::from(u8::MAX))
+        .ok_or_else(|| ParseRuleError::new(rule, "expected a state count"))?
+        .max(2) as u8;
+    let middle = match number(parts[2], 'M') {
+        Some(0) => false,
+        Some(1) => true,
+        _ => return Err(ParseRuleError::new(rule, "expected M0 or M1")),
+    };
+    let survival = range(parts[3], 'S')
+        .ok_or_else(|| ParseRuleError::new(rule, "expected a survival range like S2..3"))?;
+    let birth = range(parts[4], 'B')
+        .ok_or_else(|| ParseRuleError::new(rule, "expected a birth range like B3..3"))?;
+    if *birth.start() == 0 {
+        return Err(ParseRuleError::new(
+            rule,
+            "B0 is not supported for Larger than Life rules",
+        ));
+    }
+    let neighborhood = match prefixed(parts[5], &['N']).map(|n| n.to_ascii_uppercase()) {
+        Some(n) if n == "M" => Neighborhood::Moore,
+        Some(n) if n == "N" => Neighborhood::VonNeumann,
+        Some(n) if n == "H" => Neighborhood::Hexagonal,
+        _ => return Err(ParseRuleError::new(rule, "expected NM, NN or NH")),
+    };
+
+    Ok(Rule {
+        kind: Kind::LargerThanLife {
+            radius,
+            middle,
+            survival,
+            birth,
+        },
+        states,
+        neighborhood,
+    })
+}
+
+// parses the blocks of MCell's `MS,D...` notation
+fn parse_margolus(rule: &str, table: &str) -> Result<Rule, ParseRuleError> {
+    let blocks: Vec<u8> = table
+        .split(';')
+        .map(|block| block.trim().parse().ok().filter(|&block| block < 16))
+        .collect::<Option<_>>()
+        .ok_or_else(|| ParseRuleError::new(rule, "expected blocks from 0 to 15"))?;
+    let table: [u8; 16] = blocks[..]
+        .try_into()
+        .map_err(|_| ParseRuleError::new(rule, "expected 16 blocks"))?;
+    if table[0] != 0 && (table[0] != 15 || (table[15] != 0 && table[15] != 15)) {
+        return Err(ParseRuleError::new(
+            rule,
+            "the background must stay uniform",
+        ));
+    }
+    Ok(Rule::margolus(table))
+}
+
+impl ParseRuleError {
+    pub(crate) fn new(rule: &str, reason: &'static str) -> Self {
+        Self {
+            rule: rule.to_string(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid rule `{}`: {}", self.rule, self.reason)
+    }
+}
+
+impl Error for ParseRuleError {}
@@ -0,0 +1,807 @@
+use std::{collections::HashMap, str::FromStr};
+
+use crate::{
+    core::Cell,
+    rule::{ParseRuleError, Rule},
+};
+
+/// Rule loaded from a Golly `.rule` file, together with the metadata for viewers.
+///
+/// The transitions come from an `@TABLE` or an `@TREE` section. Tables may use the `Moore`,
+/// `vonNeumann`, `hexagonal` and `oneDimensional` neighborhoods, which are all subsets of
+/// the Moore neighborhood: hexagonal tables ignore the north east and south west neighbors,
+/// one dimensional ones everything but the west and east neighbors.
+///
+/// A cell no transition applies to keeps its state. Like for every other rule, a dead cell
+/// with only dead neighbors has to stay dead, files where it doesn't are rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleFile {
+    rule: Rule,
+    colors: Vec<(Cell, [u8; 3])>,
+    icons: Vec<Icons>,
+}
+
+/// Icons of the states from an `@ICONS` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Icons {
+    /// One of Golly's built-in shapes, such as `circles`.
+    Builtin(String),
+    /// Square icons of `size` pixels for the states from 1 on, each one row by row,
+    /// where `None` is transparent.
+    Images {
+        size: usize,
+        icons: Vec<Vec<Option<[u8; 3]>>>,
+    },
+}
+
+// positions of the neighbors in the order of the rule file, in the neighbors `Rule::next` gets
+const MOORE: &[usize] = &[1, 2, 4, 7, 6, 5, 3, 0];
+const VON_NEUMANN: &[usize] = &[1, 4, 6, 3];
+const HEXAGONAL: &[usize] = &[1, 4, 7, 6, 3, 0];
+const ONE_DIMENSIONAL: &[usize] = &[3, 4];
+
+// trees look at the neighbors in a different order
+const MOORE_TREE: &[usize] = &[0, 2, 5, 7, 1, 3, 4, 6];
+const VON_NEUMANN_TREE: &[usize] = &[1, 3, 4, 6];
+
+// the center and the Moore neighbors
+const MAX_INPUTS: usize = 9;
+
+// transition function of a rule file, which `Rule` delegates to
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Transitions {
+    pub(crate) name: String,
+    lookup: Lookup,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Lookup {
+    Table(RuleTable),
+    Tree(RuleTree),
+}
+
+// set of states, which inputs of a table transition match
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+struct States([u64; 4]);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Input {
+    states: States,
+    // inputs with the same variable match the same state
+    var: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Output {
+    State(u8),
+    // the state the variable was bound to
+    Var(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct Transition {
+    center: Input,
+    neighbors: Vec<Input>,
+    output: Output,
+}
+
+// the states the variables of a transition are bound to
+type Bindings = [Option<u8>; MAX_INPUTS];
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct RuleTable {
+    neighbors: &'static [usize],
+    // for every symmetry, which neighbor each input of a transition is compared with.
+    // `None` if the inputs may be compared with the neighbors in any order.
+    symmetries: Option<Vec<Vec<usize>>>,
+    transitions: Vec<Transition>,
+    // indices of the transitions which can apply to a center state, in the order of the file
+    by_center: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct RuleTree {
+    neighbors: &'static [usize],
+    // the root is the last node. the children of the nodes at level 1 are states,
+    // all others point to nodes one level below.
+    nodes: Vec<Vec<usize>>,
+}
+
+impl RuleFile {
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Colors of the states from the `@COLORS` section.
+    pub fn colors(&self) -> &[(Cell, [u8; 3])] {
+        &self.colors
+    }
+
+    /// Icons from the `@ICONS` section, one set for each size.
+    pub fn icons(&self) -> &[Icons] {
+        &self.icons
+    }
+}
+
+impl Transitions {
+    pub(crate) fn next(&self, cell: Cell, neighbors: &[Cell; 8]) -> Cell {
+        match &self.lookup {
+            Lookup::Table(table) => table.next(cell, neighbors),
+            Lookup::Tree(tree) => tree.next(cell, neighbors),
+        }
+    }
+}
+
+impl States {
+    fn single(state: u8) -> Self {
+        let mut states = Self::default();
+        states.insert(state);
+        states
+    }
+
+    fn insert(&mut self, state: u8) {
+        self.0[state as usize / 64] |= 1 << (state % 64);
+    }
+
+    fn contains(&self, state: u8) -> bool {
+        self.0[state as usize / 64] >> (state % 64) & 1 == 1
+    }
+
+    fn union(self, other: Self) -> Self {
+        let mut union = self;
+        for (word, other) in union.0.iter_mut().zip(other.0.iter()) {
+            *word |= other;
+        }
+        union
+    }
+}
+
+impl Input {
+    fn bind(&self, state: u8, bindings: &mut Bindings) -> bool {
+        if !self.states.contains(state) {
+            return false;
+        }
+        match self.var {
+            None => true,
+            Some(var) => match bindings[var] {
+                Some(bound) => bound == state,
+                None => {
+                    bindings[var] = Some(state);
+                    true
+                }
+            },
+        }
+    }
+}
+
+// matches `inputs` with the `states` not `used` yet, in any order
+fn bind_permuted(
+    inputs: &[Input],
+    states: &[u8],
+    used: u8,
+    bindings: Bindings,
+) -> Option<Bindings> {
+    let (input, rest) = match inputs.split_first() {
+        Some(split) => split,
+        None => return Some(bindings),
+    };
+    for (i, &state) in states.iter().enumerate() {
+        let mut bindings = bindings;
+        if used >> i & 1 == 0 && input.bind(state, &mut bindings) {
+            if let Some(bindings) = bind_permuted(rest, states, used | 1 << i, bindings) {
+                return Some(bindings);
+            }
+        }
+    }
+    None
+}
+
+impl RuleTable {
+    fn next(&self, cell: Cell, neighbors: &[Cell; 8]) -> Cell {
+        let transitions = match self.by_center.get(cell.state() as usize) {
+            Some(transitions) => transitions,
+            None => return cell,
+        };
+        let mut states = [0; 8];
+        for (state, &i) in states.iter_mut().zip(self.neighbors) {
+            *state = neighbors[i].state();
+        }
+        let states = &states[..self.neighbors.len()];
+
+        for &i in transitions {
+            let transition = &self.transitions[i];
+            let mut bindings = [None; MAX_INPUTS];
+            if !transition.center.bind(cell.state(), &mut bindings) {
+                continue;
+            }
+            let bindings = match &self.symmetries {
+                Some(symmetries) => symmetries.iter().find_map(|symmetry| {
+                    let mut bindings = bindings;
+                    let matches = transition
+                        .neighbors
+                        .iter()
+                        .zip(symmetry)
+                        .all(|(input, &j)| input.bind(states[j], &mut bindings));
+                    if matches {
+                        Some(bindings)
+                    } else {
+                        None
+                    }
+                }),
+                None => bind_permuted(&transition.neighbors, states, 0, bindings),
+            };
+            if let Some(bindings) = bindings {
+                return match transition.output {
+                    Output::State(state) => Cell::new(state),
+                    Output::Var(var) => Cell::new(bindings[var].unwrap()),
+                };
+            }
+        }
+        cell
+    }
+}
+
+impl RuleTree {
+    fn next(&self, cell: Cell, neighbors: &[Cell; 8]) -> Cell {
+        let mut node = &self.nodes[self.nodes.len() - 1];
+        for &i in self.neighbors {
+            match node.get(neighbors[i].state() as usize) {
+                Some(&child) => node = &self.nodes[child],
+                None => return cell,
+            }
+        }
+        match node.get(cell.state() as usize) {
+            Some(&state) => Cell::new(state as u8),
+            None => cell,
+        }
+    }
+}
+
+// the lines of a section without comments and blank lines
+fn content<'a>(lines: &'a [&'a str]) -> impl Iterator<Item = &'a str> {
+    lines
+        .iter()
+        .map(|line| line.split('#').next().unwrap().trim())
+        .filter(|line| !line.is_empty())
+}
+
+// the value of a `key: value` or `key = value` line
+fn value<'a>(line: &'a str, key: &str, separator: char) -> Option<&'a str> {
+    let (name, value) = line.split_at(line.find(separator)?);
+    if name.trim() == key {
+        Some(value[1..].trim())
+    } else {
+        None
+    }
+}
+
+fn parse_number<T: FromStr>(line: &str, number: &str) -> Result<T, ParseRuleError> {
+    number
+        .parse()
+        .map_err(|_| ParseRuleError::new(line, "expected a number"))
+}
+
+fn parse_states(line: &str, number: &str) -> Result<u8, ParseRuleError> {
+    match parse_number(line, number)? {
+        states @ 2..=255 => Ok(states),
+        _ => Err(ParseRuleError::new(
+            line,
+            "expected a state count from 2 to 255",
+        )),
+    }
+}
+
+// splits a transition or a set of states at commas and white space, keeping `{...}` together
+fn tokens(line: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let (mut start, mut depth) = (0, 0);
+    for (i, c) in line.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' | ' ' | '\t' if depth == 0 => {
+                tokens.push(&line[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    tokens.push(&line[start..]);
+    tokens.retain(|token| !token.is_empty());
+    tokens
+}
+
+// the states of a state, a variable or a `{...}` set of those
+fn parse_set(
+    line: &str,
+    token: &str,
+    vars: &HashMap<&str, States>,
+    states: u8,
+) -> Result<States, ParseRuleError> {
+    if token.starts_with('{') && token.ends_with('}') {
+        return tokens(&token[1..token.len() - 1])
+            .into_iter()
+            .try_fold(States::default(), |set, token| {
+                Ok(set.union(parse_set(line, token, vars, states)?))
+            });
+    }
+    if let Some(&set) = vars.get(token) {
+        return Ok(set);
+    }
+    match token.parse::<u8>() {
+        Ok(state) if state < states => Ok(States::single(state)),
+        Ok(_) => Err(ParseRuleError::new(line, "state out of range")),
+        Err(_) => Err(ParseRuleError::new(line, "unknown variable")),
+    }
+}
+
+// the permutations of the neighbors `symmetries` allows, `None` if any permutation is allowed
+fn parse_symmetries(
+    line: &str,
+    neighborhood: &str,
+    symmetries: &str,
+    neighbors: usize,
+) -> Result<Option<Vec<Vec<usize>>>, ParseRuleError> {
+    // the neighbors are listed clockwise, so rotating them rotates the neighborhood.
+    // a one dimensional neighborhood is reflected by rotating its two neighbors.
+    let (step, reflect) = match (neighborhood, symmetries) {
+        (_, "none") => (neighbors, false),
+        (_, "permute") => return Ok(None),
+        ("Moore", "rotate4") | ("hexagonal", "rotate3") => (2, false),
+        ("Moore", "rotate8") | ("vonNeumann", "rotate4") | ("hexagonal", "rotate6") => (1, false),
+        ("Moore", "rotate4reflect") => (2, true),
+        ("Moore", "rotate8reflect") | ("vonNeumann", "rotate4reflect") => (1, true),
+        ("hexagonal", "rotate6reflect") => (1, true),
+        ("Moore", "reflect_horizontal") | ("vonNeumann", "reflect_horizontal") => (neighbors, true),
+        ("hexagonal", "rotate2") => (3, false),
+        ("oneDimensional", "reflect") => (1, false),
+        _ => {
+            return Err(ParseRuleError::new(
+                line,
+                "unknown symmetries for the neighborhood",
+            ))
+        }
+    };
+
+    let mut permutations = Vec::new();
+    for rotation in (0..neighbors).step_by(step) {
+        permutations.push((0..neighbors).map(|i| (i + rotation) % neighbors).collect());
+        if reflect {
+            permutations.push(
+                (0..neighbors)
+                    .map(|i| (neighbors - i + rotation) % neighbors)
+                    .collect(),
+            );
+        }
+    }
+    Ok(Some(permutations))
+}
+
+fn parse_transition(
+    line: &str,
+    vars: &HashMap<&str, States>,
+    states: u8,
+    neighbors: usize,
+) -> Result<Transition, ParseRuleError> {
+    let mut tokens = tokens(line);
+    // states below 10 may be written without commas
+    if tokens.len() == 1 && line.len() == neighbors + 2 {
+        tokens = (0..line.len()).map(|i| &line[i..=i]).collect();
+    }
+    if tokens.len() != neighbors + 2 {
+        return Err(ParseRuleError::new(
+            line,
+            "expected the center, its neighbors and the new state",
+        ));
+    }
+
+    // every variable is bound, which only matters if it occurs more than once
+    let mut bound = Vec::new();
+    let mut inputs = Vec::new();
+    for &token in &tokens[..=neighbors] {
+        let var = if vars.contains_key(token) {
+            Some(
+                bound
+                    .iter()
+                    .position(|&var| var == token)
+                    .unwrap_or_else(|| {
+                        bound.push(token);
+                        bound.len() - 1
+                    }),
+            )
+        } else {
+            None
+        };
+        inputs.push(Input {
+            states: parse_set(line, token, vars, states)?,
+            var,
+        });
+    }
+
+    let output = tokens[neighbors + 1];
+    let output = match bound.iter().position(|&var| var == output) {
+        Some(var) => Output::Var(var),
+        None => match output.parse::<u8>() {
+            Ok(state) if state < states => Output::State(state),
+            _ => {
+                return Err(ParseRuleError::new(
+                    line,
+                    "the new state must be a state or a variable of the inputs",
+                ))
+            }
+        },
+    };
+
+    let center = inputs.remove(0);
+    Ok(Transition {
+        center,
+        neighbors: inputs,
+        output,
+    })
+}
+
+fn parse_table(name: &str, lines: &[&str]) -> Result<(Transitions, u8), ParseRuleError> {
+    let mut states = None;
+    let mut neighborhood = None;
+    let mut symmetries = None;
+    let mut vars = HashMap::new();
+    let mut transitions = Vec::new();
+
+    for line in content(lines) {
+        if let Some(number) = value(line, "n_states", ':') {
+            states = Some(parse_states(line, number)?);
+        } else if let Some(value) = value(line, "neighborhood", ':') {
+            let neighbors = match value {
+                "Moore" => MOORE,
+                "vonNeumann" => VON_NEUMANN,
+                "hexagonal" => HEXAGONAL,
+                "oneDimensional" => ONE_DIMENSIONAL,
+                _ => return Err(ParseRuleError::new(line, "unknown neighborhood")),
+            };
+            neighborhood = Some((value, neighbors));
+        } else if let Some(value) = value(line, "symmetries", ':') {
+            let (neighborhood, neighbors) = neighborhood.ok_or_else(|| {
+                ParseRuleError::new(line, "the neighborhood must come before the symmetries")
+            })?;
+            symmetries = Some(parse_symmetries(
+                line,
+                neighborhood,
+                value,
+                neighbors.len(),
+            )?);
+        } else {
+            let (states, neighbors) = match (states, neighborhood, &symmetries) {
+                (Some(states), Some((_, neighbors)), Some(_)) => (states, neighbors),
+                _ => {
+                    return Err(ParseRuleError::new(
+                        line,
+                        "expected n_states, neighborhood and symmetries first",
+                    ))
+                }
+            };
+            if let Some(var) = line.strip_prefix("var ") {
+                let (var, set) = var.split_at(
+                    var.find('=')
+                        .ok_or_else(|| ParseRuleError::new(line, "expected `var name = {...}`"))?,
+                );
+                let set = parse_set(line, set[1..].trim(), &vars, states)?;
+                vars.insert(var.trim(), set);
+            } else {
+                transitions.push(parse_transition(line, &vars, states, neighbors.len())?);
+            }
+        }
+    }
+
+    let (states, neighbors, symmetries) = match (states, neighborhood, symmetries) {
+        (Some(states), Some((_, neighbors)), Some(symmetries)) => (states, neighbors, symmetries),
+        _ => {
+            return Err(ParseRuleError::new(
+                name,
+                "the table needs n_states, neighborhood and symmetries",
+            ))
+        }
+    };
+    let mut by_center = vec![Vec::new(); states as usize];
+    for (i, transition) in transitions.iter().enumerate() {
+        for (state, transitions) in by_center.iter_mut().enumerate() {
+            if transition.center.states.contains(state as u8) {
+                transitions.push(i);
+            }
+        }
+    }
+
+    let table = RuleTable {
+        neighbors,
+        symmetries,
+        transitions,
+        by_center,
+    };
+    let transitions = Transitions {
+        name: name.to_string(),
+        lookup: Lookup::Table(table),
+    };
+    check_empty_space(&transitions)?;
+    Ok((transitions, states))
+}
+
+fn parse_tree(name: &str, lines: &[&str]) -> Result<(Transitions, u8), ParseRuleError> {
+    let mut states = None;
+    let mut neighbors = None;
+    let mut node_count = None;
+    // the nodes with their level
+    let mut nodes: Vec<(usize, Vec<usize>)> = Vec::new();
+
+    for line in content(lines) {
+        if let Some(number) = value(line, "num_states", '=') {
+            states = Some(parse_states(line, number)?);
+        } else if let Some(number) = value(line, "num_neighbors", '=') {
+            neighbors = match number {
+                "4" => Some(VON_NEUMANN_TREE),
+                "8" => Some(MOORE_TREE),
+                _ => return Err(ParseRuleError::new(line, "expected 4 or 8 neighbors")),
+            };
+        } else if let Some(number) = value(line, "num_nodes", '=') {
+            node_count = Some(parse_number::<usize>(line, number)?);
+        } else {
+            let states =
+                states.ok_or_else(|| ParseRuleError::new(line, "expected num_states first"))?;
+            let numbers = line
+                .split_whitespace()
+                .map(|number| parse_number(line, number))
+                .collect::<Result<Vec<usize>, _>>()?;
+            let (level, children) = match numbers.split_first() {
+                Some((&level, children)) if level >= 1 && children.len() == states as usize => {
+                    (level, children.to_vec())
+                }
+                _ => {
+                    return Err(ParseRuleError::new(
+                        line,
+                        "expected a level and a child for every state",
+                    ))
+                }
+            };
+            let valid = children.iter().all(|&child| {
+                if level == 1 {
+                    child < states as usize
+                } else {
+                    matches!(nodes.get(child), Some(&(child_level, _)) if child_level == level - 1)
+                }
+            });
+            if !valid {
+                return Err(ParseRuleError::new(
+                    line,
+                    "children must be states or nodes one level below",
+                ));
+            }
+            nodes.push((level, children));
+        }
+    }
+
+    let (states, neighbors) = match (states, neighbors) {
+        (Some(states), Some(neighbors)) => (states, neighbors),
+        _ => {
+            return Err(ParseRuleError::new(
+                name,
+                "the tree needs num_states and num_neighbors",
+            ))
+        }
+    };
+    if node_count != Some(nodes.len()) {
+        return Err(ParseRuleError::new(
+            name,
+            "num_nodes doesn't match the number of nodes",
+        ));
+    }
+    if !matches!(nodes.last(), Some(&(level, _)) if level == neighbors.len() + 1) {
+        return Err(ParseRuleError::new(
+            name,
+            "the last node must be the root one level above the neighbors",
+        ));
+    }
+
+    let tree = RuleTree {
+        neighbors,
+        nodes: nodes.into_iter().map(|(_, children)| children).collect(),
+    };
+    let transitions = Transitions {
+        name: name.to_string(),
+        lookup: Lookup::Tree(tree),
+    };
+    check_empty_space(&transitions)?;
+    Ok((transitions, states))
+}
+
+// rejects transitions which bring empty space to life, which would fill the whole plane
+fn check_empty_space(transitions: &Transitions) -> Result<(), ParseRuleError> {
+    if transitions.next(Cell::DEAD, &[Cell::DEAD; 8]) != Cell::DEAD {
+        return Err(ParseRuleError::new(
+            &transitions.name,
+            "a dead cell with only dead neighbors must stay dead",
+        ));
+    }
+    Ok(())
+}
+
+fn parse_colors(lines: &[&str], states: u8) -> Result<Vec<(Cell, [u8; 3])>, ParseRuleError> {
+    let mut colors = Vec::new();
+    for line in content(lines) {
+        let numbers = line
+            .split_whitespace()
+            .map(|number| parse_number(line, number))
+            .collect::<Result<Vec<u8>, _>>()?;
+        match numbers[..] {
+            [state, r, g, b] if state < states => colors.push((Cell::new(state), [r, g, b])),
+            // a gradient over the states from 1 on
+            [r1, g1, b1, r2, g2, b2] => {
+                let last = u32::from(states - 1);
+                for state in 1..=last {
+                    let mix = |from: u8, to: u8| {
+                        let (from, to) = (u32::from(from), u32::from(to));
+                        let mixed = if last == 1 {
+                            from
+                        } else {
+                            (from * (last - state) + to * (state - 1)) / (last - 1)
+                        };
+                        mixed as u8
+                    };
+                    colors.push((
+                        Cell::new(state as u8),
+                        [mix(r1, r2), mix(g1, g2), mix(b1, b2)],
+                    ));
+                }
+            }
+            _ => {
+                return Err(ParseRuleError::new(
+                    line,
+                    "expected a state and its color or a gradient",
+                ))
+            }
+        }
+    }
+    Ok(colors)
+}
+
+fn parse_color(line: &str, color: &str) -> Result<Option<[u8; 3]>, ParseRuleError> {
+    if color.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+    let hex = color
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6)
+        .ok_or_else(|| ParseRuleError::new(line, "expected a color like #RRGGBB"))?;
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .map_err(|_| ParseRuleError::new(line, "expected a color like #RRGGBB"))
+    };
+    Ok(Some([channel(0)?, channel(2)?, channel(4)?]))
+}
+
+// parses XPM images, whose lines are quoted, or the name of built-in icons
+fn parse_icons(lines: &[&str]) -> Result<Vec<Icons>, ParseRuleError> {
+    let mut icons = Vec::new();
+    let mut lines = lines.iter().map(|line| line.trim()).peekable();
+    while let Some(line) = lines.next() {
+        if line.is_empty() || line.starts_with("XPM") || line.starts_with("/*") {
+            continue;
+        }
+        if !line.starts_with('"') {
+            icons.push(Icons::Builtin(line.to_string()));
+            continue;
+        }
+
+        let mut strings = std::iter::once(line)
+            .chain(std::iter::from_fn(|| {
+                lines.next_if(|line| {
+                    line.is_empty() || line.starts_with('"') || line.starts_with("/*")
+                })
+            }))
+            // comments and blank lines have no quoted string
+            .filter_map(|line| line.split('"').nth(1));
+
+        let header = strings.next().unwrap();
+        let numbers = header
+            .split_whitespace()
+            .map(|number| parse_number(header, number))
+            .collect::<Result<Vec<usize>, _>>()?;
+        let (width, height, color_count, chars) = match numbers[..] {
+            [width, height, color_count, chars] if width > 0 && height % width == 0 => {
+                (width, height, color_count, chars)
+            }
+            _ => {
+                return Err(ParseRuleError::new(
+                    header,
+                    "expected the size of a column of square icons, its colors and characters per pixel",
+                ))
+            }
+        };
+
+        let mut palette = HashMap::new();
+        for _ in 0..color_count {
+            let line = strings
+                .next()
+                .ok_or_else(|| ParseRuleError::new(header, "missing colors"))?;
+            if line.len() < chars {
+                return Err(ParseRuleError::new(line, "expected a color"));
+            }
+            let (key, color) = line.split_at(chars);
+            let color = match color.split_whitespace().collect::<Vec<_>>()[..] {
+                ["c", color] => parse_color(line, color)?,
+                _ => return Err(ParseRuleError::new(line, "expected a color")),
+            };
+            palette.insert(key, color);
+        }
+
+        let mut pixels = Vec::new();
+        for _ in 0..height {
+            let row = strings
+                .next()
+                .ok_or_else(|| ParseRuleError::new(header, "missing rows"))?;
+            if row.len() != width * chars {
+                return Err(ParseRuleError::new(row, "row has the wrong length"));
+            }
+            for i in (0..row.len()).step_by(chars) {
+                let color = palette
+                    .get(&row[i..i + chars])
+                    .ok_or_else(|| ParseRuleError::new(row, "unknown color"))?;
+                pixels.push(*color);
+            }
+        }
+        icons.push(Icons::Images {
+            size: width,
+            icons: pixels.chunks(width * width).map(<[_]>::to_vec).collect(),
+        });
+    }
+    Ok(icons)
+}
+
+/// Parses the contents of a `.rule` file. Sections other than `@RULE`, `@TABLE`, `@TREE`,
+/// `@COLORS` and `@ICONS` are ignored.
+impl FromStr for RuleFile {
+    type Err = ParseRuleError;
+
+    fn from_str(file: &str) -> Result<Self, Self::Err> {
+        let mut sections: Vec<(&str, &str, Vec<&str>)> = Vec::new();
+        for line in file.lines() {
+            if let Some(header) = line.trim().strip_prefix('@') {
+                let mut words = header.splitn(2, char::is_whitespace);
+                let section = words.next().unwrap();
+                sections.push((section, words.next().unwrap_or("").trim(), Vec::new()));
+            } else if let Some((_, _, lines)) = sections.last_mut() {
+                lines.push(line);
+            }
+        }
+        let section = |name: &str| sections.iter().find(|(section, _, _)| *section == name);
+
+        let name = match section("RULE") {
+            Some((_, name, _)) if !name.is_empty() => *name,
+            _ => {
+                let first = file.lines().next().unwrap_or_default();
+                return Err(ParseRuleError::new(first, "expected `@RULE name`"));
+            }
+        };
+        let (transitions, states) = match (section("TABLE"), section("TREE")) {
+            (Some((_, _, lines)), _) => parse_table(name, lines)?,
+            (None, Some((_, _, lines))) => parse_tree(name, lines)?,
+            (None, None) => {
+                return Err(ParseRuleError::new(
+                    name,
+                    "expected an @TABLE or @TREE section",
+                ))
+            }
+        };
+        let colors = match section("COLORS") {
+            Some((_, _, lines)) => parse_colors(lines, states)?,
+            None => Vec::new(),
+        };
+        let icons = match section("ICONS") {
+            Some((_, _, lines)) => parse_icons(lines)?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            rule: Rule::from_file(transitions, states),
+            colors,
+            icons,
+        })
+    }
+}
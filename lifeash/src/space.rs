@@ -0,0 +1,515 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crate::{
+    core::{Cell, Level, Position},
+    rule::{prefixed, ParseRuleError, Rule},
+    universe::{Id, Universe},
+};
+
+/// Position of a cell in three-dimensional space.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Position3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Position3 {
+    pub const fn new(x: i64, y: i64, z: i64) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl From<(i64, i64, i64)> for Position3 {
+    fn from((x, y, z): (i64, i64, i64)) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+/// Outer totalistic rule on the 26 cells around a cell in space, like `B5/S45`.
+///
+/// Counts of 10 and more are written with commas between all counts, like `B5,6/S4,5,10`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SpaceRule {
+    // bit `count` is set if that many alive neighbors lead to a birth or survival
+    birth: u32,
+    survival: u32,
+}
+
+impl SpaceRule {
+    /// Creates a rule from the neighbor counts leading to a birth or survival.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a count exceeds 26, or if `birth` contains 0, which would fill space.
+    pub fn new(birth: &[u8], survival: &[u8]) -> Self {
+        assert!(!birth.contains(&0), "B0 isn't supported in space");
+        let mask = |counts: &[u8]| {
+            counts.iter().fold(0, |mask, &count| {
+                assert!(count <= 26, "there are only 26 neighbors");
+                mask | 1 << count
+            })
+        };
+        Self {
+            birth: mask(birth),
+            survival: mask(survival),
+        }
+    }
+
+    /// Whether a dead cell with `count` alive neighbors is born.
+    pub fn is_born(&self, count: u32) -> bool {
+        self.birth >> count & 1 == 1
+    }
+
+    /// Whether an alive cell with `count` alive neighbors survives.
+    pub fn survives(&self, count: u32) -> bool {
+        self.survival >> count & 1 == 1
+    }
+
+    fn next(&self, cell: Cell, count: u32) -> Cell {
+        let alive = match cell {
+            Cell::DEAD => self.is_born(count),
+            _ => self.survives(count),
+        };
+        if alive {
+            Cell::ALIVE
+        } else {
+            Cell::DEAD
+        }
+    }
+}
+
+impl Default for SpaceRule {
+    fn default() -> Self {
+        Self::new(&[5], &[4, 5])
+    }
+}
+
+impl fmt::Display for SpaceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let separator = if (self.birth | self.survival) >> 10 == 0 {
+            ""
+        } else {
+            ","
+        };
+        let counts = |mask: u32| {
+            (0..=26)
+                .filter(|count| mask >> count & 1 == 1)
+                .map(|count: u32| count.to_string())
+                .collect::<Vec<_>>()
+                .join(separator)
+        };
+        write!(f, "B{}/S{}", counts(self.birth), counts(self.survival))
+    }
+}
+
+impl FromStr for SpaceRule {
+    type Err = ParseRuleError;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = rule.trim().split('/').collect();
+        let (birth, survival) = match parts[..] {
+            [birth, survival] => (
+                prefixed(birth, &['B']).ok_or_else(|| ParseRuleError::new(rule, "expected B"))?,
+                prefixed(survival, &['S'])
+                    .ok_or_else(|| ParseRuleError::new(rule, "expected S"))?,
+            ),
+            _ => return Err(ParseRuleError::new(rule, "expected B.../S...")),
+        };
+
+        let counts = |counts: &str| -> Result<Vec<u8>, ParseRuleError> {
+            let counts: Option<Vec<u8>> = if counts.contains(',') {
+                counts.split(',').map(|count| count.parse().ok()).collect()
+            } else {
+                counts
+                    .chars()
+                    .map(|count| count.to_digit(10).map(|count| count as u8))
+                    .collect()
+            };
+            counts
+                .filter(|counts| counts.iter().all(|&count| count <= 26))
+                .ok_or_else(|| ParseRuleError::new(rule, "neighbor counts must be from 0 to 26"))
+        };
+        let (birth, survival) = (counts(birth)?, counts(survival)?);
+        if birth.contains(&0) {
+            return Err(ParseRuleError::new(rule, "B0 isn't supported in space"));
+        }
+        Ok(Self::new(&birth, &survival))
+    }
+}
+
+// hash-consed like the nodes of a `Universe`, with eight octants instead of four quadrants.
+// the octants are indexed by `z << 2 | y << 1 | x`, where 1 is the upper half of an axis.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum SpaceNode {
+    Leaf(Cell),
+    Inode {
+        level: Level,
+        population: u32,
+        octants: [Id; 8],
+    },
+}
+
+impl SpaceNode {
+    fn level(self) -> Level {
+        match self {
+            SpaceNode::Leaf(_) => Level::LEAF_LEVEL,
+            SpaceNode::Inode { level, .. } => level,
+        }
+    }
+
+    fn population(self) -> u32 {
+        match self {
+            SpaceNode::Leaf(cell) => !cell.is_dead() as u32,
+            SpaceNode::Inode { population, .. } => population,
+        }
+    }
+
+    fn octants(self) -> [Id; 8] {
+        match self {
+            SpaceNode::Inode { octants, .. } => octants,
+            SpaceNode::Leaf(_) => panic!("not an inode"),
+        }
+    }
+}
+
+// the octant of the cell at `x`, `y`, `z` of a cube `side` cells wide,
+// with the coordinates within that octant
+fn octant(side: i64, (x, y, z): (i64, i64, i64)) -> (usize, (i64, i64, i64)) {
+    let half = side / 2;
+    let index = ((z >= half) as usize) << 2 | ((y >= half) as usize) << 1 | (x >= half) as usize;
+    (index, (x % half, y % half, z % half))
+}
+
+/// A pattern in infinite three-dimensional space together with its generation, evolved by
+/// hashlife on an octree.
+///
+/// Like [`Universe`], every call to [`SpaceUniverse::evolve`] advances a single generation,
+/// and the nodes and their memoized results are kept for all later generations.
+#[derive(Debug, Clone)]
+pub struct SpaceUniverse {
+    rule: SpaceRule,
+    nodes: Vec<SpaceNode>,
+    ids: HashMap<SpaceNode, Id>,
+    // the center half of a node one generation later
+    results: HashMap<Id, Id>,
+    root: Id,
+    generation: usize,
+}
+
+impl SpaceUniverse {
+    /// Creates empty space running `rule`.
+    pub fn new(rule: SpaceRule) -> Self {
+        let mut universe = Self {
+            rule,
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            results: HashMap::new(),
+            root: Id(0),
+            generation: 0,
+        };
+        universe.root = universe.new_empty_tree(Level::new(3));
+        universe
+    }
+
+    pub fn rule(&self) -> SpaceRule {
+        self.rule
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Number of alive cells.
+    pub fn population(&self) -> u32 {
+        self.node(self.root).population()
+    }
+
+    pub fn set_cell(&mut self, pos: impl Into<Position3>, cell: Cell) {
+        let pos = pos.into();
+        while !self.contains(pos) {
+            self.root = self.expand(self.root);
+        }
+        let level = self.node(self.root).level();
+        let min = level.min_coord();
+        let offset = (pos.x - min, pos.y - min, pos.z - min);
+        self.root = self.set_tree_cell(self.root, offset, cell);
+    }
+
+    pub fn get_cell(&self, pos: impl Into<Position3>) -> Cell {
+        let pos = pos.into();
+        if !self.contains(pos) {
+            return Cell::DEAD;
+        }
+        let min = self.node(self.root).level().min_coord();
+        let (mut tree, mut offset) = (self.root, (pos.x - min, pos.y - min, pos.z - min));
+        loop {
+            match self.node(tree) {
+                SpaceNode::Leaf(cell) => return cell,
+                SpaceNode::Inode { level, octants, .. } => {
+                    let (index, inner) = octant(level.side_len() as i64, offset);
+                    tree = octants[index];
+                    offset = inner;
+                }
+            }
+        }
+    }
+
+    /// Positions of all alive cells.
+    pub fn alive_cells(&self) -> Vec<Position3> {
+        let mut cells = Vec::new();
+        let min = self.node(self.root).level().min_coord();
+        self.for_each_alive(self.root, Position3::new(min, min, min), None, &mut |pos| {
+            cells.push(pos)
+        });
+        cells
+    }
+
+    /// The plane of cells at depth `z`, as a [`Universe`] for viewing.
+    ///
+    /// The universe runs a rule without births or survivals, as evolving a slice of space
+    /// on its own is meaningless.
+    pub fn slice(&self, z: i64) -> Universe {
+        let mut plane = Universe::with_rule(Rule::new(&[], &[], 2));
+        plane.initialize();
+        let min = self.node(self.root).level().min_coord();
+        self.for_each_alive(
+            self.root,
+            Position3::new(min, min, min),
+            Some(z),
+            &mut |pos| plane.set_cell(Position::new(pos.x, pos.y), Cell::ALIVE),
+        );
+        plane
+    }
+
+    pub fn evolve(&mut self) {
+        let mut root = self.root;
+        // the result of the root is its center half, so the population has to stay within
+        // the center quarter to have room to grow by a cell in every direction
+        loop {
+            if self.node(root).level() >= 3 {
+                let center = self.centered(root, (3, 3, 3));
+                if self.node(center).population() == self.node(root).population() {
+                    break;
+                }
+            }
+            root = self.expand(root);
+        }
+
+        self.root = self.evolve_tree(root);
+        self.generation += 1;
+    }
+
+    fn contains(&self, pos: Position3) -> bool {
+        let range = self.node(self.root).level().coord_range();
+        range.contains(&pos.x) && range.contains(&pos.y) && range.contains(&pos.z)
+    }
+
+    fn node(&self, id: Id) -> SpaceNode {
+        self.nodes[id.0]
+    }
+
+    fn get_id(&mut self, node: SpaceNode) -> Id {
+        if let Some(&id) = self.ids.get(&node) {
+            return id;
+        }
+        let id = Id(self.nodes.len());
+        self.nodes.push(node);
+        self.ids.insert(node, id);
+        id
+    }
+
+    fn new_leaf(&mut self, cell: Cell) -> Id {
+        self.get_id(SpaceNode::Leaf(cell))
+    }
+
+    fn new_inode(&mut self, octants: [Id; 8]) -> Id {
+        let level = self.node(octants[0]).level() + 1;
+        debug_assert!(octants.iter().all(|&id| self.node(id).level() + 1 == level));
+        let population = octants.iter().map(|&id| self.node(id).population()).sum();
+        self.get_id(SpaceNode::Inode {
+            level,
+            population,
+            octants,
+        })
+    }
+
+    fn new_empty_tree(&mut self, level: Level) -> Id {
+        if level == Level::LEAF_LEVEL {
+            self.new_leaf(Cell::DEAD)
+        } else {
+            let octant = self.new_empty_tree(level - 1);
+            self.new_inode([octant; 8])
+        }
+    }
+
+    // wraps `tree` into a tree one level higher, keeping it centered
+    fn expand(&mut self, tree: Id) -> Id {
+        let node = self.node(tree);
+        let border = self.new_empty_tree(node.level() - 1);
+        let mut octants = node.octants();
+        for (index, octant) in octants.iter_mut().enumerate() {
+            // each octant moves into the opposite corner of a new octant
+            let mut corner = [border; 8];
+            corner[7 - index] = *octant;
+            *octant = self.new_inode(corner);
+        }
+        self.new_inode(octants)
+    }
+
+    // sets the cell at `offset` from the lower corner of `tree`
+    fn set_tree_cell(&mut self, tree: Id, offset: (i64, i64, i64), cell: Cell) -> Id {
+        match self.node(tree) {
+            SpaceNode::Leaf(_) => self.new_leaf(cell),
+            SpaceNode::Inode { level, octants, .. } => {
+                let (index, inner) = octant(level.side_len() as i64, offset);
+                let mut octants = octants;
+                octants[index] = self.set_tree_cell(octants[index], inner, cell);
+                self.new_inode(octants)
+            }
+        }
+    }
+
+    // calls `f` with the position of every alive cell in `tree`, whose lower corner is at `min`,
+    // only looking at the plane at depth `z` if there is one
+    fn for_each_alive(
+        &self,
+        tree: Id,
+        min: Position3,
+        z: Option<i64>,
+        f: &mut impl FnMut(Position3),
+    ) {
+        match self.node(tree) {
+            SpaceNode::Leaf(cell) => {
+                if !cell.is_dead() && z.is_none_or(|z| z == min.z) {
+                    f(min)
+                }
+            }
+            SpaceNode::Inode {
+                level,
+                population,
+                octants,
+            } => {
+                if population == 0 {
+                    return;
+                }
+                let half = (level.side_len() / 2) as i64;
+                for (index, &octant) in octants.iter().enumerate() {
+                    let corner = Position3::new(
+                        min.x + (index & 1) as i64 * half,
+                        min.y + (index >> 1 & 1) as i64 * half,
+                        min.z + (index >> 2) as i64 * half,
+                    );
+                    if z.is_none_or(|z| (corner.z..corner.z + half).contains(&z)) {
+                        self.for_each_alive(octant, corner, z, f);
+                    }
+                }
+            }
+        }
+    }
+
+    // the subtree `depth` levels below `tree` at `index` in units of its side
+    fn descend(&self, tree: Id, depth: u8, index: (i64, i64, i64)) -> Id {
+        if depth == 0 {
+            return tree;
+        }
+        let (octant, inner) = octant(1 << depth, index);
+        self.descend(self.node(tree).octants()[octant], depth - 1, inner)
+    }
+
+    // the subtree two levels below `tree` whose lower corner is at `corner`,
+    // in units of an eighth of the side of `tree`
+    fn centered(&mut self, tree: Id, (x, y, z): (i64, i64, i64)) -> Id {
+        let mut octants = [tree; 8];
+        for (index, octant) in octants.iter_mut().enumerate() {
+            let (dx, dy, dz) = (
+                (index & 1) as i64,
+                (index >> 1 & 1) as i64,
+                (index >> 2) as i64,
+            );
+            *octant = self.descend(tree, 3, (x + dx, y + dy, z + dz));
+        }
+        self.new_inode(octants)
+    }
+
+    // the center half of `tree`, which is at least at level 2, one generation later
+    fn evolve_tree(&mut self, tree: Id) -> Id {
+        if let Some(&result) = self.results.get(&tree) {
+            return result;
+        }
+
+        let node = self.node(tree);
+        let result = if node.population() == 0 {
+            self.new_empty_tree(node.level() - 1)
+        } else if node.level() == 2 {
+            self.manual_evolve(tree)
+        } else {
+            // the 27 centered subtrees at a quarter of the size of `tree`, which overlap
+            // to 8 subtrees at half the size, whose results make up the result of `tree`
+            let mut subs = HashMap::new();
+            for z in 0..3 {
+                for y in 0..3 {
+                    for x in 0..3 {
+                        let sub = self.centered(tree, (1 + 2 * x, 1 + 2 * y, 1 + 2 * z));
+                        subs.insert((x, y, z), sub);
+                    }
+                }
+            }
+            let mut octants = [tree; 8];
+            for (index, octant) in octants.iter_mut().enumerate() {
+                let (x, y, z) = (
+                    (index & 1) as i64,
+                    (index >> 1 & 1) as i64,
+                    (index >> 2) as i64,
+                );
+                let mut overlapping = [tree; 8];
+                for (inner, sub) in overlapping.iter_mut().enumerate() {
+                    let (dx, dy, dz) = (
+                        (inner & 1) as i64,
+                        (inner >> 1 & 1) as i64,
+                        (inner >> 2) as i64,
+                    );
+                    *sub = subs[&(x + dx, y + dy, z + dz)];
+                }
+                let overlapping = self.new_inode(overlapping);
+                *octant = self.evolve_tree(overlapping);
+            }
+            self.new_inode(octants)
+        };
+        self.results.insert(tree, result);
+        result
+    }
+
+    // evolves the center of a node at level 2 cell by cell, looking at all 26 neighbors
+    fn manual_evolve(&mut self, tree: Id) -> Id {
+        let cell = |x: i64, y: i64, z: i64| match self.node(self.descend(tree, 2, (x, y, z))) {
+            SpaceNode::Leaf(cell) => cell,
+            SpaceNode::Inode { .. } => unreachable!(),
+        };
+        let mut next = [Cell::DEAD; 8];
+        for (index, next) in next.iter_mut().enumerate() {
+            let (x, y, z) = (
+                1 + (index & 1) as i64,
+                1 + (index >> 1 & 1) as i64,
+                1 + (index >> 2) as i64,
+            );
+            let mut count = 0;
+            for dz in -1..=1 {
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx, dy, dz) != (0, 0, 0) && cell(x + dx, y + dy, z + dz) == Cell::ALIVE
+                        {
+                            count += 1;
+                        }
+                    }
+                }
+            }
+            *next = self.rule.next(cell(x, y, z), count);
+        }
+        let mut octants = [tree; 8];
+        for (octant, &cell) in octants.iter_mut().zip(&next) {
+            *octant = self.new_leaf(cell);
+        }
+        self.new_inode(octants)
+    }
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use bimap::BiMap;
+
+use crate::{
+    core::Cell,
+    node::Node,
+    rule::Rule,
+    table::{ReadTable, Table},
+    universe::Id,
+    view::NodeLog,
+};
+
+/// Hash-consed node storage together with the memoized results of the nodes.
+///
+/// The results depend on the rule, so the rule belongs to the store. A store can be shared
+/// by many [`Universe`](crate::Universe)s running that rule, which then reuse each other's
+/// interned nodes and results. Nodes are never reclaimed at the moment;
+/// a future collector has to treat the root of every universe sharing the store as live.
+///
+/// Rules with `B0` are emulated: the nodes hold the difference of the cells to the
+/// background, which is evolved by one of two rules depending on the background.
+/// Margolus rules are run by another two rules in odd generations, whose blocks start at
+/// odd coordinates.
+pub struct NodeStore {
+    pub(crate) table: BiMap<Id, Node>,
+    // every interned node once more, for views to read without locking the store
+    pub(crate) log: Arc<NodeLog>,
+    // the id of the next interned node. a parallel evolution leaves gaps, so this can be
+    // past the number of nodes.
+    pub(crate) next_id: usize,
+    pub(crate) rule: Rule,
+    // the emulating rules for each phase, see `Rule::phase`
+    phases: [Rule; 4],
+    // the phase of the generation being evolved, which selects the emulating rule
+    pub(crate) phase: usize,
+    // results of the later phases, the nodes only hold the ones of phase 0
+    phase_results: HashMap<(usize, Id), Id>,
+}
+
+/// Handle to a [`NodeStore`] which can be shared between universes and threads.
+#[derive(Clone)]
+pub struct SharedStore {
+    store: Arc<Mutex<NodeStore>>,
+    log: Arc<NodeLog>,
+}
+
+impl NodeStore {
+    pub fn new() -> Self {
+        Self::with_rule(Rule::life())
+    }
+
+    pub fn with_rule(rule: Rule) -> Self {
+        Self {
+            table: BiMap::new(),
+            log: Arc::new(NodeLog::new(rule.clone())),
+            next_id: 0,
+            phases: [0, 1, 2, 3].map(|phase| {
+                rule.in_generation(phase >> 1)
+                    .relative_to(Cell::new(phase as u8 & 1))
+            }),
+            rule,
+            phase: 0,
+            phase_results: HashMap::new(),
+        }
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Number of interned nodes.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+}
+
+impl Default for NodeStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SharedStore {
+    pub fn new() -> Self {
+        Self::with_rule(Rule::life())
+    }
+
+    pub fn with_rule(rule: Rule) -> Self {
+        let store = NodeStore::with_rule(rule);
+        Self {
+            log: store.log.clone(),
+            store: Arc::new(Mutex::new(store)),
+        }
+    }
+
+    pub fn lock(&self) -> MutexGuard<'_, NodeStore> {
+        self.store.lock().unwrap()
+    }
+
+    // the nodes of the store, which can be read without locking it
+    pub(crate) fn log(&self) -> &Arc<NodeLog> {
+        &self.log
+    }
+
+    // locks the store, set up to evolve a universe at `generation`
+    pub(crate) fn lock_for_evolution(&self, generation: usize) -> MutexGuard<'_, NodeStore> {
+        let mut store = self.lock();
+        store.phase = store.rule.phase(generation);
+        store
+    }
+
+    /// Whether both handles point to the same store.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.store, &other.store)
+    }
+}
+
+impl Default for SharedStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReadTable for NodeStore {
+    fn node(&self, id: Id) -> Node {
+        *self.table.get_by_left(&id).unwrap()
+    }
+}
+
+impl Table for NodeStore {
+    fn rule(&self) -> &Rule {
+        &self.phases[self.phase]
+    }
+
+    fn get_id(&mut self, node: Node) -> Id {
+        if let Some(id) = self.table.get_by_right(&node) {
+            *id
+        } else {
+            let id = Id(self.next_id);
+            self.next_id += 1;
+            self.table.insert(id, node);
+            self.log.insert(id, node);
+            id
+        }
+    }
+
+    fn result(&self, id: Id) -> Option<Id> {
+        if self.phase != 0 {
+            return self.phase_results.get(&(self.phase, id)).copied();
+        }
+        id.inode(self).result
+    }
+
+    fn set_result(&mut self, id: Id, result: Id) {
+        if self.phase != 0 {
+            self.phase_results.insert((self.phase, id), result);
+            return;
+        }
+        if let (id, Node::Inode(mut inode)) = self.table.remove_by_left(&id).unwrap() {
+            inode.result = Some(result);
+            self.table.insert(id, Node::Inode(inode));
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt;
+
+use crate::{
+    core::{Offset, Position},
+    pattern::{Pattern, Transform},
+    universe::Universe,
+};
+
+/// The rotations and reflections mapping a pattern onto itself, see [`Pattern::symmetry`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symmetry {
+    /// The transforms fixing the pattern, in the order of [`Transform::ALL`], always
+    /// starting with the identity.
+    pub transforms: Vec<Transform>,
+    /// Twice the centre of the bounding box, which all rotations turn around and all
+    /// mirror axes pass through. Doubled so that centres between cells are exact.
+    pub double_center: Position,
+    width: i64,
+    height: i64,
+}
+
+impl Symmetry {
+    pub fn contains(&self, transform: Transform) -> bool {
+        self.transforms.contains(&transform)
+    }
+
+    /// The name of the symmetry class as used by soup searches, like `C1`, `C2_4`, `D4_+2`
+    /// or `D8_1`.
+    ///
+    /// The number after a rotation tells whether its centre is on a cell (1), on the edge
+    /// between two cells (2) or on the corner of four (4). The number after orthogonal mirror
+    /// axes counts the cells around their crossing, like for rotations, or tells whether a
+    /// single axis runs through cells (1) or between them (2).
+    pub fn class(&self) -> &'static str {
+        use Transform::*;
+        let odd = (self.width & 1 == 1, self.height & 1 == 1);
+        let has = |transform| self.contains(transform);
+        if has(Rotate90) && has(FlipHorizontal) {
+            if odd.0 {
+                "D8_1"
+            } else {
+                "D8_4"
+            }
+        } else if has(Rotate90) {
+            if odd.0 {
+                "C4_1"
+            } else {
+                "C4_4"
+            }
+        } else if has(FlipHorizontal) && has(FlipVertical) {
+            match odd {
+                (true, true) => "D4_+1",
+                (false, false) => "D4_+4",
+                _ => "D4_+2",
+            }
+        } else if has(FlipDiagonal) && has(FlipAntidiagonal) {
+            if odd.0 {
+                "D4_x1"
+            } else {
+                "D4_x4"
+            }
+        } else if has(Rotate180) {
+            match odd {
+                (true, true) => "C2_1",
+                (false, false) => "C2_4",
+                _ => "C2_2",
+            }
+        } else if has(FlipHorizontal) {
+            if odd.0 {
+                "D2_+1"
+            } else {
+                "D2_+2"
+            }
+        } else if has(FlipVertical) {
+            if odd.1 {
+                "D2_+1"
+            } else {
+                "D2_+2"
+            }
+        } else if has(FlipDiagonal) || has(FlipAntidiagonal) {
+            "D2_x"
+        } else {
+            "C1"
+        }
+    }
+}
+
+impl fmt::Display for Symmetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} around ({}, {})/2",
+            self.class(),
+            self.double_center.x,
+            self.double_center.y
+        )
+    }
+}
+
+/// A later phase of a pattern which is a rotated or reflected copy of the current one,
+/// see [`Universe::phase_symmetry`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PhaseSymmetry {
+    pub generations: usize,
+    /// The cell at `pos` now is at `transform.apply(pos) + offset` after the generations,
+    /// taking `pos` as an offset from the origin.
+    pub transform: Transform,
+    pub offset: Offset,
+}
+
+impl PhaseSymmetry {
+    /// Whether the copy is mirrored, like in flip oscillators and gliding spaceships.
+    pub fn is_reflection(&self) -> bool {
+        !matches!(
+            self.transform,
+            Transform::Identity | Transform::Rotate90 | Transform::Rotate180 | Transform::Rotate270
+        )
+    }
+}
+
+impl Pattern {
+    /// The rotations and reflections which map the pattern onto itself after moving it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pattern is empty.
+    pub fn symmetry(&self) -> Symmetry {
+        let (min, max) = self.bounds();
+        let (normalized, _) = self.normalized();
+        let transforms = Transform::ALL
+            .iter()
+            .copied()
+            .filter(|&transform| self.transformed(transform).normalized().0 == normalized)
+            .collect();
+        Symmetry {
+            transforms,
+            double_center: Position::new(min.dx + max.dx, min.dy + max.dy),
+            width: max.dx - min.dx + 1,
+            height: max.dy - min.dy + 1,
+        }
+    }
+}
+
+impl Universe {
+    /// The symmetry of the cells which aren't dead, see [`Pattern::symmetry`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the universe is empty.
+    pub fn symmetry(&self) -> Symmetry {
+        self.to_pattern().symmetry()
+    }
+
+    /// Evolves a copy of the universe for up to `period` generations, and returns the first
+    /// phase which is a rotated or reflected copy of the current one, like half way through
+    /// the period of a glider or a flip oscillator.
+    ///
+    /// Returns `None` if the pattern repeats itself before, or none of the phases is such a
+    /// copy. Transforms are tried in the order of [`Transform::ALL`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the universe is empty.
+    pub fn phase_symmetry(&self, period: usize) -> Option<PhaseSymmetry> {
+        let start = self.to_pattern();
+        let (normalized, _) = start.normalized();
+        let mut universe = self.clone();
+        for generations in 1..period {
+            universe.evolve();
+            let phase = universe.to_pattern();
+            if phase.is_empty() {
+                return None;
+            }
+            let (phase, shift) = phase.normalized();
+            if phase == normalized {
+                return None;
+            }
+            for &transform in &Transform::ALL[1..] {
+                let (image, image_shift) = start.transformed(transform).normalized();
+                if image == phase {
+                    return Some(PhaseSymmetry {
+                        generations,
+                        transform,
+                        offset: shift - image_shift,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,782 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::{
+    core::{Cell, Level, Offset, Position, Quadrant::*},
+    node::{Inode, Leaf, Node},
+    rule::Rule,
+    universe::Id,
+};
+
+thread_local! {
+    // the cells of a node at the base level, of its next center and the running sums of its
+    // rows, kept around for the next node so `Table::evolve_cells` doesn't allocate for each one
+    static SCRATCH: RefCell<(Vec<Cell>, Vec<Cell>, Vec<u32>)> =
+        const { RefCell::new((Vec::new(), Vec::new(), Vec::new())) };
+}
+
+// progress of an evolution is measured in finished results this many levels below the root
+pub(crate) const PROGRESS_DEPTH: u32 = 2;
+
+// controls an evolution from within the recursion.
+pub(crate) trait Limit {
+    // called before every result which has to be computed
+    fn proceed(&mut self) -> bool;
+
+    // called when the result of a subtree `depth` levels below the root is known.
+    // subtrees at `PROGRESS_DEPTH` are always reported, all others only if they didn't recurse.
+    fn finished(&mut self, depth: u32);
+
+    // called with every node at the base level whose `result` was just computed
+    fn evaluated(&mut self, _node: Id, _result: Id) {}
+}
+
+struct Unlimited;
+
+impl Limit for Unlimited {
+    #[inline(always)]
+    fn proceed(&mut self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn finished(&mut self, _depth: u32) {}
+}
+
+// queries only need to resolve ids, which frozen views can do as well.
+pub(crate) trait ReadTable {
+    fn node(&self, id: Id) -> Node;
+
+    fn get_tree_cell(&self, tree: Id, pos: impl Into<Position>) -> Cell {
+        let pos = pos.into();
+        match self.node(tree) {
+            Node::Leaf(c) => c.0,
+            Node::Inode(Inode {
+                level,
+                population: _,
+                result: _,
+                nw,
+                ne,
+                sw,
+                se,
+            }) => match pos.quadrant() {
+                NorthWest => {
+                    self.get_tree_cell(nw, pos.relative_to(level.quadrant_center(NorthWest)))
+                }
+                NorthEast => {
+                    self.get_tree_cell(ne, pos.relative_to(level.quadrant_center(NorthEast)))
+                }
+                SouthWest => {
+                    self.get_tree_cell(sw, pos.relative_to(level.quadrant_center(SouthWest)))
+                }
+                SouthEast => {
+                    self.get_tree_cell(se, pos.relative_to(level.quadrant_center(SouthEast)))
+                }
+            },
+        }
+    }
+
+    // calls `f` with the position of every alive cell in `tree`, whose north west corner is at `min`
+    fn for_each_alive(&self, tree: Id, min: Position, f: &mut impl FnMut(Position)) {
+        match self.node(tree) {
+            Node::Leaf(leaf) => {
+                if !leaf.0.is_dead() {
+                    f(min)
+                }
+            }
+            Node::Inode(inode) => {
+                if inode.population == 0 {
+                    return;
+                }
+                let half = (inode.level.side_len() / 2) as i64;
+                self.for_each_alive(inode.nw, min, f);
+                self.for_each_alive(inode.ne, min + Offset::new(half, 0), f);
+                self.for_each_alive(inode.sw, min + Offset::new(0, half), f);
+                self.for_each_alive(inode.se, min + Offset::new(half, half), f);
+            }
+        }
+    }
+
+    // counts the cells of `tree`, whose north west corner is at `corner`, in the rectangle
+    // from `min` to `max`, using the population of every subtree lying fully inside of it
+    fn population_in(&self, tree: Id, corner: Position, min: Position, max: Position) -> u32 {
+        let node = self.node(tree);
+        let side = node.level().side_len() as i64;
+        let far = corner + Offset::new(side - 1, side - 1);
+        if node.population() == 0
+            || far.x < min.x
+            || far.y < min.y
+            || corner.x > max.x
+            || corner.y > max.y
+        {
+            return 0;
+        }
+        if min.x <= corner.x && min.y <= corner.y && far.x <= max.x && far.y <= max.y {
+            return node.population();
+        }
+
+        // a partial overlap needs more than one cell
+        let inode = tree.inode(self);
+        let half = side / 2;
+        self.population_in(inode.nw, corner, min, max)
+            + self.population_in(inode.ne, corner + Offset::new(half, 0), min, max)
+            + self.population_in(inode.sw, corner + Offset::new(0, half), min, max)
+            + self.population_in(inode.se, corner + Offset::new(half, half), min, max)
+    }
+
+    // the populations of the squares `1 << shift` cells wide which tile the rectangle from
+    // `min` to `max`, row by row, where `tree` has its north west corner at `corner`. squares
+    // of 2^63 cells and more all cover the whole rectangle, so larger shifts don't overflow.
+    fn density_grid(
+        &self,
+        tree: Id,
+        corner: Position,
+        min: Position,
+        max: Position,
+        shift: u32,
+    ) -> Vec<u32> {
+        if min.x > max.x || min.y > max.y {
+            return Vec::new();
+        }
+        let shift = shift.min(63);
+        let width = ((max.x - min.x) >> shift) as usize + 1;
+        let height = ((max.y - min.y) >> shift) as usize + 1;
+        let mut density = vec![0; width * height];
+        self.add_density(tree, corner, min, max, shift, &mut density);
+        density
+    }
+
+    // adds the cells of `tree`, whose north west corner is at `corner`, in the rectangle from
+    // `min` to `max` to the pixels of `density`, squares `1 << shift` cells wide tiling the
+    // rectangle. subtrees inside of a single pixel add their population at once.
+    fn add_density(
+        &self,
+        tree: Id,
+        corner: Position,
+        min: Position,
+        max: Position,
+        shift: u32,
+        density: &mut [u32],
+    ) {
+        let node = self.node(tree);
+        let side = node.level().side_len() as i64;
+        let far = corner + Offset::new(side - 1, side - 1);
+        if node.population() == 0
+            || far.x < min.x
+            || far.y < min.y
+            || corner.x > max.x
+            || corner.y > max.y
+        {
+            return;
+        }
+        let inside = min.x <= corner.x && min.y <= corner.y && far.x <= max.x && far.y <= max.y;
+        let pixel = |pos: Position| ((pos.x - min.x) >> shift, (pos.y - min.y) >> shift);
+        if inside && pixel(corner) == pixel(far) {
+            let (x, y) = pixel(corner);
+            let width = ((max.x - min.x) >> shift) as usize + 1;
+            density[y as usize * width + x as usize] += node.population();
+            return;
+        }
+
+        let inode = tree.inode(self);
+        let half = side / 2;
+        for &(child, offset) in &[
+            (inode.nw, Offset::new(0, 0)),
+            (inode.ne, Offset::new(half, 0)),
+            (inode.sw, Offset::new(0, half)),
+            (inode.se, Offset::new(half, half)),
+        ] {
+            self.add_density(child, corner + offset, min, max, shift, density);
+        }
+    }
+
+    // adds the number of cells in each state of `tree` to `census`.
+    // subtrees repeat a lot, so their counts are memoized in `known`.
+    fn census(&self, tree: Id, census: &mut [u64], known: &mut HashMap<Id, Vec<u64>>) {
+        if let Some(counts) = known.get(&tree) {
+            census
+                .iter_mut()
+                .zip(counts)
+                .for_each(|(n, count)| *n += count);
+            return;
+        }
+
+        let mut counts = vec![0; census.len()];
+        match self.node(tree) {
+            Node::Leaf(leaf) => {
+                if !leaf.0.is_dead() {
+                    counts[leaf.0.state() as usize] = 1;
+                }
+            }
+            Node::Inode(inode) => {
+                if inode.population != 0 {
+                    for &child in &[inode.nw, inode.ne, inode.sw, inode.se] {
+                        self.census(child, &mut counts, known);
+                    }
+                }
+            }
+        }
+        census
+            .iter_mut()
+            .zip(&counts)
+            .for_each(|(n, count)| *n += count);
+        known.insert(tree, counts);
+    }
+}
+
+// the hashlife algorithm only needs a way to hash-cons nodes and to memoize results.
+// keeping it generic over the storage lets the serial and the parallel evolution share
+// every line of the recursion, so both produce bit-identical results.
+pub(crate) trait Table: ReadTable {
+    fn rule(&self) -> &Rule;
+
+    fn get_id(&mut self, node: Node) -> Id;
+
+    fn result(&self, id: Id) -> Option<Id>;
+
+    fn set_result(&mut self, id: Id, result: Id);
+
+    // the level evolved directly instead of recursing, the lowest one whose center
+    // is a quarter of its side, and so the radius of the rule, away from its edges.
+    // nodes from level 3 on start at even coordinates, which Margolus rules need to find
+    // the blocks of their partition.
+    fn base_level(&self) -> Level {
+        let mut level = Level::new(if self.rule().is_margolus() { 3 } else { 2 });
+        while level.side_len() / 4 < u64::from(self.rule().radius()) {
+            level += 1;
+        }
+        level
+    }
+
+    fn new_leaf(&mut self, cell: Cell) -> Id {
+        let node = Node::Leaf(Leaf::new(cell));
+        self.get_id(node)
+    }
+
+    fn new_inode(&mut self, nwx: Id, nex: Id, swx: Id, sex: Id) -> Id {
+        let childs = (
+            self.node(nwx),
+            self.node(nex),
+            self.node(swx),
+            self.node(sex),
+        );
+        let inode = match childs {
+            (Node::Inode(nw), Node::Inode(ne), Node::Inode(sw), Node::Inode(se)) => {
+                debug_assert!(nw.level == ne.level && ne.level == sw.level && sw.level == se.level);
+                Inode {
+                    level: nw.level + 1,
+                    population: nw.population + ne.population + sw.population + se.population,
+                    result: None,
+                    nw: nwx,
+                    ne: nex,
+                    sw: swx,
+                    se: sex,
+                }
+            }
+            (Node::Leaf(nw), Node::Leaf(ne), Node::Leaf(sw), Node::Leaf(se)) => Inode {
+                level: Level::new(1),
+                population: [nw, ne, sw, se].iter().filter(|c| !c.0.is_dead()).count() as u32,
+                result: None,
+                nw: nwx,
+                ne: nex,
+                sw: swx,
+                se: sex,
+            },
+            _ => unreachable!(),
+        };
+
+        self.get_id(Node::Inode(inode))
+    }
+
+    fn new_empty_tree(&mut self, level: Level) -> Id {
+        if level == Level::LEAF_LEVEL {
+            self.new_leaf(Cell::DEAD)
+        } else {
+            let child = self.new_empty_tree(level - 1);
+            self.new_inode(child, child, child, child)
+        }
+    }
+
+    fn set_tree_cell(&mut self, tree: Id, pos: impl Into<Position>, state: Cell) -> Id {
+        let pos = pos.into();
+
+        match self.node(tree) {
+            Node::Leaf(_) => self.new_leaf(state),
+            Node::Inode(Inode {
+                level,
+                population: _,
+                result: _,
+                nw,
+                ne,
+                sw,
+                se,
+            }) => match pos.quadrant() {
+                NorthWest => {
+                    let nw = self.set_tree_cell(
+                        nw,
+                        pos.relative_to(level.quadrant_center(NorthWest)),
+                        state,
+                    );
+                    self.new_inode(nw, ne, sw, se)
+                }
+                NorthEast => {
+                    let ne = self.set_tree_cell(
+                        ne,
+                        pos.relative_to(level.quadrant_center(NorthEast)),
+                        state,
+                    );
+                    self.new_inode(nw, ne, sw, se)
+                }
+                SouthWest => {
+                    let sw = self.set_tree_cell(
+                        sw,
+                        pos.relative_to(level.quadrant_center(SouthWest)),
+                        state,
+                    );
+                    self.new_inode(nw, ne, sw, se)
+                }
+                SouthEast => {
+                    let se = self.set_tree_cell(
+                        se,
+                        pos.relative_to(level.quadrant_center(SouthEast)),
+                        state,
+                    );
+                    self.new_inode(nw, ne, sw, se)
+                }
+            },
+        }
+    }
+
+    // wraps `root` into a tree one level higher, keeping it centered
+    fn expand(&mut self, root: Id) -> Id {
+        let root = root.inode(self);
+        let border = self.new_empty_tree(root.level - 1);
+        let (nw, ne, sw, se) = (
+            self.new_inode(border, border, border, root.nw),
+            self.new_inode(border, border, root.ne, border),
+            self.new_inode(border, root.sw, border, border),
+            self.new_inode(root.se, border, border, border),
+        );
+        self.new_inode(nw, ne, sw, se)
+    }
+
+    // sets the `cells` of `tree`, whose north west corner is at `corner`, building each node
+    // above them once instead of once per cell
+    fn set_cells(&mut self, tree: Id, corner: Position, cells: &mut [(Position, Cell)]) -> Id {
+        let inode = match self.node(tree) {
+            _ if cells.is_empty() => return tree,
+            Node::Leaf(_) => return self.new_leaf(cells[cells.len() - 1].1),
+            Node::Inode(inode) => inode,
+        };
+        let half = (inode.level.side_len() / 2) as i64;
+        let center = corner + Offset::new(half, half);
+        // the cells of each quadrant in a row, in the order nw, ne, sw, se
+        let quadrant_of =
+            |pos: Position| ((pos.y >= center.y) as usize) << 1 | (pos.x >= center.x) as usize;
+        cells.sort_by_key(|&(pos, _)| quadrant_of(pos));
+        let mut children = [inode.nw, inode.ne, inode.sw, inode.se];
+        let mut rest = cells;
+        for (quadrant, child) in children.iter_mut().enumerate() {
+            let count = rest
+                .iter()
+                .take_while(|&&(pos, _)| quadrant_of(pos) == quadrant)
+                .count();
+            let (quadrant_cells, others) = rest.split_at_mut(count);
+            let offset = Offset::new((quadrant & 1) as i64 * half, (quadrant >> 1) as i64 * half);
+            *child = self.set_cells(*child, corner + offset, quadrant_cells);
+            rest = others;
+        }
+        self.new_inode(children[0], children[1], children[2], children[3])
+    }
+
+    // clears every cell of `tree`, whose north west corner is at `corner`, outside of the
+    // rectangle from `min` to `max`
+    fn clip(&mut self, tree: Id, corner: Position, min: Position, max: Position) -> Id {
+        let node = self.node(tree);
+        let side = node.level().side_len() as i64;
+        let far = corner + Offset::new(side - 1, side - 1);
+        let inside = min.x <= corner.x && min.y <= corner.y && far.x <= max.x && far.y <= max.y;
+        if node.population() == 0 || inside {
+            return tree;
+        }
+        if far.x < min.x || far.y < min.y || corner.x > max.x || corner.y > max.y {
+            return self.new_empty_tree(node.level());
+        }
+
+        // a partial overlap needs more than one cell
+        let inode = tree.inode(self);
+        let half = side / 2;
+        let nw = self.clip(inode.nw, corner, min, max);
+        let ne = self.clip(inode.ne, corner + Offset::new(half, 0), min, max);
+        let sw = self.clip(inode.sw, corner + Offset::new(0, half), min, max);
+        let se = self.clip(inode.se, corner + Offset::new(half, half), min, max);
+        self.new_inode(nw, ne, sw, se)
+    }
+
+    // replaces `root` by its centered subnode as long as that holds the whole population,
+    // down to the level of a fresh universe
+    fn shrink(&mut self, mut root: Id) -> Id {
+        loop {
+            let iroot = root.inode(self);
+            if iroot.level <= 3 {
+                return root;
+            }
+            let center = [
+                iroot.nw.inode(self).se,
+                iroot.ne.inode(self).sw,
+                iroot.sw.inode(self).ne,
+                iroot.se.inode(self).nw,
+            ];
+            let center_pop: u32 = center.iter().map(|id| id.node(self).population()).sum();
+            if center_pop != iroot.population {
+                return root;
+            }
+            root = self.new_inode(center[0], center[1], center[2], center[3]);
+        }
+    }
+
+    // expands the root until all of its population lies in the innermost quarter,
+    // so that the result of the root contains the whole next generation.
+    fn expand_for_evolution(&mut self, mut root: Id) -> Id {
+        loop {
+            let iroot = root.inode(self);
+            // the population checks below look three levels deep, and the margin around the
+            // innermost quarter must cover the radius of the rule
+            if iroot.level <= self.base_level() {
+                root = self.expand(root);
+                continue;
+            }
+
+            let (nw_pop, ne_pop, sw_pop, se_pop) = (
+                iroot.nw.node(self).population(),
+                iroot.ne.node(self).population(),
+                iroot.sw.node(self).population(),
+                iroot.se.node(self).population(),
+            );
+
+            let (nw_inner_pop, ne_inner_pop, sw_inner_pop, se_inner_pop) = (
+                iroot
+                    .nw
+                    .inode(self)
+                    .se
+                    .inode(self)
+                    .se
+                    .node(self)
+                    .population(),
+                iroot
+                    .ne
+                    .inode(self)
+                    .sw
+                    .inode(self)
+                    .sw
+                    .node(self)
+                    .population(),
+                iroot
+                    .sw
+                    .inode(self)
+                    .ne
+                    .inode(self)
+                    .ne
+                    .node(self)
+                    .population(),
+                iroot
+                    .se
+                    .inode(self)
+                    .nw
+                    .inode(self)
+                    .nw
+                    .node(self)
+                    .population(),
+            );
+
+            if nw_pop == nw_inner_pop
+                && ne_pop == ne_inner_pop
+                && sw_pop == sw_inner_pop
+                && se_pop == se_inner_pop
+            {
+                return root;
+            }
+            root = self.expand(root);
+        }
+    }
+
+    // since recursive make second function which always calls on root
+    fn evolve_tree(&mut self, tree: Id) -> Id {
+        self.try_evolve_tree(tree, 0, &mut Unlimited).unwrap()
+    }
+
+    // evolves `tree`, which lies `depth` levels below the root, as long as `limit` allows it.
+    // on interruption every result computed so far stays memoized.
+    fn try_evolve_tree(&mut self, tree: Id, depth: u32, limit: &mut impl Limit) -> Option<Id> {
+        debug_assert!(
+            tree.inode(self).level >= self.base_level(),
+            "must be at the base level or higher"
+        );
+
+        if let Some(result) = self.result(tree) {
+            limit.finished(depth);
+            return Some(result);
+        }
+
+        // only the results computed here are charged, not the way down to them, so every
+        // retry after an interruption gets further than the one before
+        if tree.inode(self).level == self.base_level() {
+            if !limit.proceed() {
+                return None;
+            }
+            let result = self.manual_evolve(tree);
+            self.set_result(tree, result);
+            limit.evaluated(tree, result);
+            limit.finished(depth);
+            Some(result)
+        } else {
+            let (nw, ne, sw, se) = self.overlapping_subs(tree);
+            let (nw, ne, sw, se) = (
+                self.try_evolve_tree(nw, depth + 1, limit)?,
+                self.try_evolve_tree(ne, depth + 1, limit)?,
+                self.try_evolve_tree(sw, depth + 1, limit)?,
+                self.try_evolve_tree(se, depth + 1, limit)?,
+            );
+            if !limit.proceed() {
+                return None;
+            }
+            let result = self.new_inode(nw, ne, sw, se);
+            self.set_result(tree, result);
+            if depth == PROGRESS_DEPTH {
+                limit.finished(depth);
+            }
+            Some(result)
+        }
+    }
+
+    // builds the nine centered sub-results and combines them into the four overlapping
+    // nodes whose evolutions make up the result of `tree`.
+    fn overlapping_subs(&mut self, tree: Id) -> (Id, Id, Id, Id) {
+        let Inode {
+            nw: tree_nw,
+            ne: tree_ne,
+            sw: tree_sw,
+            se: tree_se,
+            ..
+        } = tree.inode(self);
+        let n00 = self.centered_sub(tree_nw);
+        let n01 = self.centered_horizontal(tree_nw, tree_ne);
+        let n02 = self.centered_sub(tree_ne);
+        let n10 = self.centered_vertical(tree_nw, tree_sw);
+        let n11 = self.centered_subsub(tree);
+        let n12 = self.centered_vertical(tree_ne, tree_se);
+        let n20 = self.centered_sub(tree_sw);
+        let n21 = self.centered_horizontal(tree_sw, tree_se);
+        let n22 = self.centered_sub(tree_se);
+
+        (
+            self.new_inode(n00, n01, n10, n11),
+            self.new_inode(n01, n02, n11, n12),
+            self.new_inode(n10, n11, n20, n21),
+            self.new_inode(n11, n12, n21, n22),
+        )
+    }
+
+    // evolves the center of a node at the base level. for Life this is a node at level 2
+    // with 16 cells,
+    // p is at position (-2, -2) and a at (1, 1)
+
+    // p o n m
+    // l k j i
+    // h g f e
+    // d c b a
+
+    // the result are the four cells k, j, g and f in the middle.
+
+    fn manual_evolve(&mut self, node: Id) -> Id {
+        let inode = node.inode(self);
+        debug_assert!(
+            inode.level == self.base_level(),
+            "manual evolution only at the base level possible"
+        );
+        if inode.population == 0 {
+            return self.new_empty_tree(inode.level - 1);
+        }
+        match self.rule().two_state_table() {
+            Some(table) if inode.level == Level::new(2) => self.evolve_bits(node, &table),
+            _ => self.evolve_cells(node),
+        }
+    }
+
+    // evolves a node at level 2 of a two-state isotropic rule with radius 1, with the 16
+    // cells as bits `y * 4 + x` indexing the table of the rule directly
+    fn evolve_bits(&mut self, node: Id, table: &[u64; 8]) -> Id {
+        let inode = node.inode(self);
+        let mut bits = 0u16;
+        for (i, &quadrant) in [inode.nw, inode.ne, inode.sw, inode.se].iter().enumerate() {
+            let quadrant = quadrant.inode(self);
+            for (j, &leaf) in [quadrant.nw, quadrant.ne, quadrant.sw, quadrant.se]
+                .iter()
+                .enumerate()
+            {
+                if leaf.node(self).population() != 0 {
+                    let (x, y) = ((i & 1) * 2 + (j & 1), (i >> 1) * 2 + (j >> 1));
+                    bits |= 1 << (y * 4 + x);
+                }
+            }
+        }
+
+        let mut next = |x: usize, y: usize| {
+            let alive =
+                |dx: usize, dy: usize| (bits >> ((y + dy - 1) * 4 + x + dx - 1) & 1) as usize;
+            // the center comes first in the table, the neighbors from the north west after it
+            let neighborhood = [
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (0, 1),
+                (2, 1),
+                (0, 2),
+                (1, 2),
+                (2, 2),
+            ]
+            .iter()
+            .fold(0, |neighborhood, &(dx, dy)| {
+                neighborhood << 1 | alive(dx, dy)
+            });
+            let index = alive(1, 1) << 8 | neighborhood;
+            self.new_leaf(Cell::new((table[index / 64] >> (index % 64) & 1) as u8))
+        };
+        let (nw, ne, sw, se) = (next(1, 1), next(2, 1), next(1, 2), next(2, 2));
+        self.new_inode(nw, ne, sw, se)
+    }
+
+    // evolves the center of a node at the base level cell by cell, for rules with more
+    // states, a larger radius or blocks
+    fn evolve_cells(&mut self, node: Id) -> Id {
+        let level = node.inode(self).level;
+        let side = level.side_len() as usize;
+        let min = level.min_pos();
+        SCRATCH.with(|scratch| {
+            let (cells, next, sums) = &mut *scratch.borrow_mut();
+            cells.clear();
+            cells.resize(side * side, Cell::DEAD);
+            let table = &*self;
+            table.for_each_alive(node, min, &mut |pos| {
+                cells[(pos.y - min.y) as usize * side + (pos.x - min.x) as usize] =
+                    table.get_tree_cell(node, pos)
+            });
+
+            let rule = self.rule();
+            let quarter = side / 4;
+            next.clear();
+            next.resize(side * side / 4, Cell::DEAD);
+            if rule.is_larger_than_life() {
+                // the alive cells of each row before each column, so that each row of the
+                // neighborhood takes a single difference instead of a cell each
+                sums.clear();
+                sums.resize(side * (side + 1), 0);
+                for (row, cells) in sums.chunks_mut(side + 1).zip(cells.chunks(side)) {
+                    for (x, &cell) in cells.iter().enumerate() {
+                        row[x + 1] = row[x] + (cell == Cell::ALIVE) as u32;
+                    }
+                }
+                let radius = i64::from(rule.radius());
+                for y in 0..side / 2 {
+                    for x in 0..side / 2 {
+                        let (cx, cy) = ((x + quarter) as i64, (y + quarter) as i64);
+                        let cell = cells[cy as usize * side + cx as usize];
+                        let mut count = 0;
+                        for dy in -radius..=radius {
+                            let row = (cy + dy) as usize * (side + 1);
+                            let reach = rule.reach(dy);
+                            let (first, last) = (cx + reach.start(), cx + reach.end());
+                            count += sums[row + last as usize + 1] - sums[row + first as usize];
+                        }
+                        // the sums count the cell itself
+                        let count = count - (cell == Cell::ALIVE) as u32;
+                        next[y * side / 2 + x] = rule.next_with_count(cell, count);
+                    }
+                }
+            } else {
+                for y in 0..side / 2 {
+                    for x in 0..side / 2 {
+                        let (cx, cy) = (x + quarter, y + quarter);
+                        // the node starts at even coordinates, see `base_level`
+                        let pos = Position::new(cx as i64, cy as i64);
+                        next[y * side / 2 + x] =
+                            rule.next_at(pos, cells[cy * side + cx], |offset| {
+                                let (nx, ny) = (cx as i64 + offset.dx, cy as i64 + offset.dy);
+                                cells[ny as usize * side + nx as usize]
+                            });
+                    }
+                }
+            }
+            self.build_tree(next, side / 2, (0, 0), level - 1)
+        })
+    }
+
+    // builds the tree at `level` for the square of `cells`, rows of `side` cells, whose
+    // north west corner is at `x`, `y`
+    fn build_tree(
+        &mut self,
+        cells: &[Cell],
+        side: usize,
+        (x, y): (usize, usize),
+        level: Level,
+    ) -> Id {
+        if level == Level::LEAF_LEVEL {
+            return self.new_leaf(cells[y * side + x]);
+        }
+        let half = (level.side_len() / 2) as usize;
+        let nw = self.build_tree(cells, side, (x, y), level - 1);
+        let ne = self.build_tree(cells, side, (x + half, y), level - 1);
+        let sw = self.build_tree(cells, side, (x, y + half), level - 1);
+        let se = self.build_tree(cells, side, (x + half, y + half), level - 1);
+        self.new_inode(nw, ne, sw, se)
+    }
+
+    // this can move into another class, when the refactoring of the leaves to Bool8x8 has been done.
+    fn centered_horizontal(&mut self, west: Id, east: Id) -> Id {
+        let (west, east) = (west.inode(self), east.inode(self));
+        debug_assert!(west.level == east.level, "levels must be the same");
+
+        let (nw, ne, sw, se) = (
+            west.ne.inode(self).se,
+            east.nw.inode(self).sw,
+            west.se.inode(self).ne,
+            east.sw.inode(self).nw,
+        );
+        self.new_inode(nw, ne, sw, se)
+    }
+
+    fn centered_vertical(&mut self, north: Id, south: Id) -> Id {
+        let (north, south) = (north.inode(self), south.inode(self));
+        debug_assert!(north.level == south.level, "levels must be the same");
+
+        let (nw, ne, sw, se) = (
+            north.sw.inode(self).se,
+            north.se.inode(self).sw,
+            south.nw.inode(self).ne,
+            south.ne.inode(self).nw,
+        );
+        self.new_inode(nw, ne, sw, se)
+    }
+
+    fn centered_sub(&mut self, node: Id) -> Id {
+        let node = node.inode(self);
+
+        let (nw, ne, sw, se) = (
+            node.nw.inode(self).se,
+            node.ne.inode(self).sw,
+            node.sw.inode(self).ne,
+            node.se.inode(self).nw,
+        );
+        self.new_inode(nw, ne, sw, se)
+    }
+
+    fn centered_subsub(&mut self, node: Id) -> Id {
+        let node = node.inode(self);
+        let (nw, ne, sw, se) = (
+            node.nw.inode(self).se.inode(self).se,
+            node.ne.inode(self).sw.inode(self).sw,
+            node.sw.inode(self).ne.inode(self).ne,
+            node.se.inode(self).nw.inode(self).nw,
+        );
+        self.new_inode(nw, ne, sw, se)
+    }
+}
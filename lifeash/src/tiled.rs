@@ -0,0 +1,234 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    core::{Cell, Position},
+    engine::Engine,
+    rule::Rule,
+    universe::Universe,
+};
+
+const TILE_SIZE: i64 = 64;
+
+// one bit per cell, bit `x` of row `y` is the cell (x, y) relative to the north west corner
+type Tile = [u64; TILE_SIZE as usize];
+
+const EMPTY_TILE: Tile = [0; TILE_SIZE as usize];
+
+// position of a tile in units of tiles
+type TilePos = (i64, i64);
+
+/// Bit-parallel engine for chaotic patterns with little repetition, where hashlife has to
+/// intern fresh nodes every generation.
+///
+/// The plane is split into tiles of 64x64 cells, each row of a tile being a single `u64`,
+/// so a whole row is updated with a few bitwise operations. Only tiles which changed in the
+/// last generation, together with their neighbors, are recomputed.
+///
+/// Only Conway's Game of Life is supported.
+#[derive(Debug, Clone, Default)]
+pub struct TiledUniverse {
+    tiles: HashMap<TilePos, Tile>,
+    // tiles which changed since the last generation
+    active: HashSet<TilePos>,
+    generation: usize,
+}
+
+fn split(pos: Position) -> (TilePos, usize, usize) {
+    (
+        (pos.x.div_euclid(TILE_SIZE), pos.y.div_euclid(TILE_SIZE)),
+        pos.x.rem_euclid(TILE_SIZE) as usize,
+        pos.y.rem_euclid(TILE_SIZE) as usize,
+    )
+}
+
+// adds a bitboard of neighbors to the counters, which count modulo 8 per bit.
+// eight neighbors wrap around to zero, which is just as dead as eight.
+#[inline(always)]
+fn add(ones: &mut u64, twos: &mut u64, fours: &mut u64, neighbors: u64) {
+    let ones_carry = *ones & neighbors;
+    *ones ^= neighbors;
+    let twos_carry = *twos & ones_carry;
+    *twos ^= ones_carry;
+    *fours ^= twos_carry;
+}
+
+impl TiledUniverse {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_cell(&mut self, pos: impl Into<Position>, cell: Cell) {
+        let (tile_pos, x, y) = split(pos.into());
+        let tile = self.tiles.entry(tile_pos).or_insert(EMPTY_TILE);
+        match cell {
+            Cell::DEAD => tile[y] &= !(1 << x),
+            Cell::ALIVE => tile[y] |= 1 << x,
+            _ => panic!("the tiled engine only knows dead and alive cells"),
+        }
+        self.active.insert(tile_pos);
+    }
+
+    pub fn get_cell(&self, pos: impl Into<Position>) -> Cell {
+        let (tile_pos, x, y) = split(pos.into());
+        match self.tiles.get(&tile_pos) {
+            Some(tile) if tile[y] >> x & 1 == 1 => Cell::ALIVE,
+            _ => Cell::DEAD,
+        }
+    }
+
+    pub fn evolve(&mut self) {
+        let mut candidates = HashSet::new();
+        for &(tx, ty) in &self.active {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    candidates.insert((tx + dx, ty + dy));
+                }
+            }
+        }
+
+        let mut next = Vec::new();
+        for tile_pos in candidates {
+            let tile = self.next_tile(tile_pos);
+            if tile != *self.tiles.get(&tile_pos).unwrap_or(&EMPTY_TILE) {
+                next.push((tile_pos, tile));
+            }
+        }
+
+        self.active.clear();
+        for (tile_pos, tile) in next {
+            if tile == EMPTY_TILE {
+                self.tiles.remove(&tile_pos);
+            } else {
+                self.tiles.insert(tile_pos, tile);
+            }
+            self.active.insert(tile_pos);
+        }
+        self.generation += 1;
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Number of alive cells.
+    pub fn population(&self) -> u32 {
+        self.tiles
+            .values()
+            .flat_map(|tile| tile.iter())
+            .map(|row| row.count_ones())
+            .sum()
+    }
+
+    pub fn alive_cells(&self) -> Vec<Position> {
+        let mut cells = Vec::new();
+        for (&(tx, ty), tile) in &self.tiles {
+            for (y, &row) in tile.iter().enumerate() {
+                let mut row = row;
+                while row != 0 {
+                    let x = row.trailing_zeros() as i64;
+                    cells.push(Position::new(tx * TILE_SIZE + x, ty * TILE_SIZE + y as i64));
+                    row &= row - 1;
+                }
+            }
+        }
+        cells
+    }
+
+    fn next_tile(&self, tile_pos: TilePos) -> Tile {
+        let (tx, ty) = tile_pos;
+        // the tile and its neighbors, row by row from the north west, looked up once
+        let mut around = [[&EMPTY_TILE; 3]; 3];
+        for (dy, tiles) in around.iter_mut().enumerate() {
+            for (dx, tile) in tiles.iter_mut().enumerate() {
+                if let Some(found) = self.tiles.get(&(tx + dx as i64 - 1, ty + dy as i64 - 1)) {
+                    *tile = found;
+                }
+            }
+        }
+        // row `y` of the tiles in `column`, continuing into the tiles north and south
+        let row = |column: usize, y: i64| {
+            let (tile, y) = (y.div_euclid(TILE_SIZE) + 1, y.rem_euclid(TILE_SIZE));
+            around[tile as usize][column][y as usize]
+        };
+
+        let mut tile = EMPTY_TILE;
+        for (y, next) in tile.iter_mut().enumerate() {
+            let y = y as i64;
+            let mut ones = 0;
+            let mut twos = 0;
+            let mut fours = 0;
+            let mut center = 0;
+            for dy in -1..=1 {
+                let (west, middle, east) = (row(0, y + dy), row(1, y + dy), row(2, y + dy));
+
+                // align the west and east neighbor of every cell with the cell itself
+                add(&mut ones, &mut twos, &mut fours, middle << 1 | west >> 63);
+                add(&mut ones, &mut twos, &mut fours, middle >> 1 | east << 63);
+                if dy == 0 {
+                    center = middle;
+                } else {
+                    add(&mut ones, &mut twos, &mut fours, middle);
+                }
+            }
+            // alive with three neighbors, or with two if it was alive already
+            *next = !fours & twos & (ones | center);
+        }
+        tile
+    }
+}
+
+impl Engine for TiledUniverse {
+    fn set_cell(&mut self, pos: Position, cell: Cell) {
+        TiledUniverse::set_cell(self, pos, cell)
+    }
+
+    fn get_cell(&self, pos: Position) -> Cell {
+        TiledUniverse::get_cell(self, pos)
+    }
+
+    fn evolve(&mut self) {
+        TiledUniverse::evolve(self)
+    }
+
+    fn generation(&self) -> usize {
+        TiledUniverse::generation(self)
+    }
+
+    fn population(&self) -> u32 {
+        TiledUniverse::population(self)
+    }
+}
+
+/// # Panics
+///
+/// Panics if the universe doesn't run Conway's Game of Life, or lives on a bounded grid.
+impl From<&Universe> for TiledUniverse {
+    fn from(universe: &Universe) -> Self {
+        assert!(
+            universe.rule() == Rule::life(),
+            "the tiled engine only runs Conway's Game of Life"
+        );
+        assert!(
+            universe.grid().is_none(),
+            "the tiled engine only runs on the infinite plane"
+        );
+        let mut tiled = Self::new();
+        for pos in universe.alive_cells() {
+            tiled.set_cell(pos, Cell::ALIVE);
+        }
+        tiled.generation = universe.generation();
+        tiled
+    }
+}
+
+impl From<&TiledUniverse> for Universe {
+    fn from(tiled: &TiledUniverse) -> Self {
+        let mut universe = Universe::new();
+        universe.initialize();
+        for pos in tiled.alive_cells() {
+            universe.set_cell(pos, Cell::ALIVE);
+        }
+        universe.generation = tiled.generation;
+        universe
+    }
+}
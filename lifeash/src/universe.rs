@@ -1,36 +1,54 @@
-use bimap::BiMap;
+use std::collections::HashMap;
 
 use crate::{
-    core::{Cell, Level, Position, Quadrant::*},
+    core::{Cell, Level, Position},
+    grid::BoundedGrid,
     node::{Inode, Leaf, Node},
+    rule::Rule,
+    store::SharedStore,
+    table::{ReadTable, Table},
 };
 
-#[derive(Default)]
+/// A pattern on the infinite plane, or on a [`BoundedGrid`], together with its generation.
+///
+/// With `B0` rules the infinite background can be alive, see [`Universe::background`].
+/// Queries for counts or lists of cells then describe the cells differing from the background.
+///
+/// Cloning a universe is cheap: the clone shares the [`NodeStore`](crate::NodeStore)
+/// and diverges from there.
+#[derive(Clone, Default)]
 pub struct Universe {
-    table: BiMap<Id, Node>,
-    root: Option<Id>,
-    generation: usize,
+    pub(crate) store: SharedStore,
+    pub(crate) root: Option<Id>,
+    pub(crate) generation: usize,
+    pub(crate) grid: Option<BoundedGrid>,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct Id(usize);
+pub struct Id(pub(crate) usize);
+
+// converts between a cell and its difference to the `background`, which is how the nodes
+// store it. only two state rules can have an alive background, where this swaps dead and alive.
+pub(crate) fn relative(cell: Cell, background: Cell) -> Cell {
+    Cell::new(cell.state() ^ background.state())
+}
 
 impl Id {
-    fn node(self, univ: &Universe) -> &Node {
-        univ.table.get_by_left(&self).unwrap()
+    pub(crate) fn node(self, table: &(impl ReadTable + ?Sized)) -> Node {
+        table.node(self)
     }
 
     #[allow(dead_code)]
-    fn leaf(self, univ: &Universe) -> &Leaf {
-        if let Node::Leaf(leaf) = self.node(univ) {
+    pub(crate) fn leaf(self, table: &(impl ReadTable + ?Sized)) -> Leaf {
+        if let Node::Leaf(leaf) = self.node(table) {
             leaf
         } else {
             panic!("not a leaf")
         }
     }
 
-    fn inode(self, univ: &Universe) -> &Inode {
-        if let Node::Inode(inode) = self.node(univ) {
+    pub(crate) fn inode(self, table: &(impl ReadTable + ?Sized)) -> Inode {
+        if let Node::Inode(inode) = self.node(table) {
             inode
         } else {
             panic!("not an inode")
@@ -40,431 +58,179 @@ impl Id {
 
 impl Universe {
     pub fn new() -> Self {
+        Self::with_store(&SharedStore::new())
+    }
+
+    /// Creates an empty universe running `rule`.
+    pub fn with_rule(rule: Rule) -> Self {
+        Self::with_store(&SharedStore::with_rule(rule))
+    }
+
+    /// Creates an empty universe whose nodes and memoized results live in `store`.
+    pub fn with_store(store: &SharedStore) -> Self {
         Self {
-            table: BiMap::new(),
+            store: store.clone(),
             root: None,
             generation: 0,
+            grid: None,
         }
     }
 
     // TODO: remove
     pub fn initialize(&mut self) {
-        self.root = Some(self.new_empty_tree(Level::new(3)));
+        self.root = Some(self.store.lock().new_empty_tree(Level::new(3)));
     }
-}
 
-impl Universe {
-    fn get_id(&mut self, node: Node) -> Id {
-        if let Some(id) = self.table.get_by_right(&node)
-        {
-            *id
-        } else {
-            let id = Id(self.table.len());
-            self.table.insert(id, node);
-            id
-        }
+    pub fn store(&self) -> &SharedStore {
+        &self.store
     }
 
-    fn new_leaf(&mut self, cell: Cell) -> Id {
-        let node = Node::Leaf(Leaf::new(cell));
-        self.get_id(node)
+    pub fn generation(&self) -> usize {
+        self.generation
     }
 
-    fn new_inode(&mut self, nwx: Id, nex: Id, swx: Id, sex: Id) -> Id {
-        let childs = (
-            nwx.node(self),
-            nex.node(self),
-            swx.node(self),
-            sex.node(self),
-        );
-        let inode = match childs {
-            (Node::Inode(nw), Node::Inode(ne), Node::Inode(sw), Node::Inode(se)) => {
-                debug_assert!(nw.level == ne.level && ne.level == sw.level && sw.level == se.level);
-                Inode {
-                    level: nw.level + 1,
-                    population: nw.population + ne.population + sw.population + se.population,
-                    result: None,
-                    nw: nwx,
-                    ne: nex,
-                    sw: swx,
-                    se: sex,
-                }
-            }
-            (Node::Leaf(nw), Node::Leaf(ne), Node::Leaf(sw), Node::Leaf(se)) => Inode {
-                level: Level::new(1),
-                population: [nw, ne, sw, se]
-                    .iter()
-                    .filter(|c| matches!(c.0, Cell::Alive))
-                    .count() as u32,
-                result: None,
-                nw: nwx,
-                ne: nex,
-                sw: swx,
-                se: sex,
-            },
-            _ => unreachable!(),
-        };
-
-        self.get_id(Node::Inode(inode))
+    pub fn rule(&self) -> Rule {
+        self.store.lock().rule().clone()
     }
 
-    fn new_empty_tree(&mut self, level: Level) -> Id {
-        if level == Level::LEAF_LEVEL {
-            self.new_leaf(Cell::Dead)
-        } else {
-            let child = Self::new_empty_tree(self, level - 1);
-            self.new_inode(child, child, child, child)
-        }
+    /// State of all cells outside of the pattern, which is only alive with `B0` rules.
+    pub fn background(&self) -> Cell {
+        self.store.lock().rule.background(self.generation)
     }
 }
 
+// Universe API
 impl Universe {
-    fn get_tree_cell(&self, tree: Id, pos: impl Into<Position>) -> Cell {
+    /// Sets the cell at `pos`, unless it lies outside of the bounded grid.
+    pub fn set_cell(&mut self, pos: impl Into<Position>, cell: Cell) {
         let pos = pos.into();
-        match *tree.node(self) {
-            Node::Leaf(c) => c.0,
-            Node::Inode(Inode {
-                level,
-                population: _,
-                result: _,
-                nw,
-                ne,
-                sw,
-                se,
-            }) => match pos.quadrant() {
-                NorthWest => {
-                    self.get_tree_cell(nw, pos.relative_to(level.quadrant_center(NorthWest)))
-                }
-                NorthEast => {
-                    self.get_tree_cell(ne, pos.relative_to(level.quadrant_center(NorthEast)))
-                }
-                SouthWest => {
-                    self.get_tree_cell(sw, pos.relative_to(level.quadrant_center(SouthWest)))
-                }
-                SouthEast => {
-                    self.get_tree_cell(se, pos.relative_to(level.quadrant_center(SouthEast)))
-                }
-            },
+        if self.grid.is_some_and(|grid| !grid.contains(pos)) {
+            return;
         }
-    }
+        let mut store = self.store.lock();
+        let mut root = self.root.unwrap();
 
-    fn set_tree_cell(&mut self, tree: Id, pos: impl Into<Position>, state: Cell) -> Id {
-        let pos = pos.into();
-
-        match *tree.node(self) {
-            Node::Leaf(_) => self.new_leaf(state),
-            Node::Inode(Inode {
-                level,
-                population: _,
-                result: _,
-                nw,
-                ne,
-                sw,
-                se,
-            }) => match pos.quadrant() {
-                NorthWest => {
-                    let nw = self.set_tree_cell(
-                        nw,
-                        pos.relative_to(level.quadrant_center(NorthWest)),
-                        state,
-                    );
-                    self.new_inode(nw, ne, sw, se)
-                }
-                NorthEast => {
-                    let ne = self.set_tree_cell(
-                        ne,
-                        pos.relative_to(level.quadrant_center(NorthEast)),
-                        state,
-                    );
-                    self.new_inode(nw, ne, sw, se)
-                }
-                SouthWest => {
-                    let sw = self.set_tree_cell(
-                        sw,
-                        pos.relative_to(level.quadrant_center(SouthWest)),
-                        state,
-                    );
-                    self.new_inode(nw, ne, sw, se)
-                }
-                SouthEast => {
-                    let se = self.set_tree_cell(
-                        se,
-                        pos.relative_to(level.quadrant_center(SouthEast)),
-                        state,
-                    );
-                    self.new_inode(nw, ne, sw, se)
-                }
-            },
+        while !pos.in_bounds(root.node(&*store).level()) {
+            root = store.expand(root);
         }
-    }
-}
 
-impl Universe {
-    fn expand(&mut self) {
-        let level = self.root.unwrap().inode(self).level;
-        let border = self.new_empty_tree(level - 1);
-        let (root_nw, root_ne, root_sw, root_se) = {
-            let root = self.root.unwrap().inode(self);
-            (root.nw, root.ne, root.sw, root.se)
-        };
-        let (nw, ne, sw, se) = (
-            self.new_inode(border, border, border, root_nw),
-            self.new_inode(border, border, root_ne, border),
-            self.new_inode(border, root_sw, border, border),
-            self.new_inode(root_se, border, border, border),
-        );
-        self.root = Some(self.new_inode(nw, ne, sw, se));
+        let cell = relative(cell, store.rule.background(self.generation));
+        self.root = Some(store.set_tree_cell(root, pos, cell));
     }
 
-    // since recursive make second function which always calls on root
-    fn evolve_tree(&mut self, tree: Id) -> Id {
-        {
-            let inode = tree.inode(self);
-            debug_assert!(inode.level >= Level::new(2), "must be level 2 or higher");
-        }
-
-        if let Some(result) = tree.inode(self).result {
-            return result;
-        }
-
-        if tree.inode(self).level == 2 {
-            self.manual_evolve(tree)
+    pub fn get_cell(&self, pos: impl Into<Position>) -> Cell {
+        let pos = pos.into();
+        let store = self.store.lock();
+        let root = self.root.unwrap();
+        let on_grid = self.grid.is_none_or(|grid| grid.contains(pos));
+        let cell = if on_grid && pos.in_bounds(root.node(&*store).level()) {
+            store.get_tree_cell(root, pos)
         } else {
-            let (tree_nw, tree_ne, tree_sw, tree_se) = {
-                let inode = tree.inode(self);
-                (inode.nw, inode.ne, inode.sw, inode.se)
-            };
-            let n00 = self.centered_sub(tree_nw);
-            let n01 = self.centered_horizontal(tree_nw, tree_ne);
-            let n02 = self.centered_sub(tree_ne);
-            let n10 = self.centered_vertical(tree_nw, tree_sw);
-            let n11 = self.centered_subsub(tree);
-            let n12 = self.centered_vertical(tree_ne, tree_se);
-            let n20 = self.centered_sub(tree_sw);
-            let n21 = self.centered_horizontal(tree_sw, tree_se);
-            let n22 = self.centered_sub(tree_se);
-
-            let (nw, ne, sw, se) = {
-                let nw = self.new_inode(n00, n01, n10, n11);
-                let ne = self.new_inode(n01, n02, n11, n12);
-                let sw = self.new_inode(n10, n11, n20, n21);
-                let se = self.new_inode(n11, n12, n21, n22);
-                (
-                    self.evolve_tree(nw),
-                    self.evolve_tree(ne),
-                    self.evolve_tree(sw),
-                    self.evolve_tree(se),
-                )
-            };
-            let result = self.new_inode(nw, ne, sw, se);
-
-            if let (id, Node::Inode(mut inode)) = self.table.remove_by_left(&tree).unwrap() {
-                inode.result = Some(result);
-                self.table.insert(id, Node::Inode(inode));
-            }
-
-            result
-        }
+            Cell::DEAD
+        };
+        relative(cell, store.rule.background(self.generation))
     }
 
-    // Inode at level 2 contains 16 cells
-    // these can be represented by a bitmap of u16
-    // p is at position (-2, -2) and a at (1, 1)
-
-    // p o n m
-    // l k j i
-    // h g f e
-    // d c b a
-    // 0b_ponm_lkji_hgfe_dcba
-
-    fn manual_evolve(&mut self, node: Id) -> Id {
-        let inode = node.inode(self);
-        debug_assert!(
-            inode.level == 2,
-            "manual evolution only at level 2 possible"
-        );
-
-        let mut all_bits: u16 = 0;
-        for y in -2..2 {
-            for x in -2..2 {
-                all_bits = (all_bits << 1) + self.get_tree_cell(node, (x, y)) as u16;
-            }
-        }
-        let (nw, ne, sw, se) = (
-            self.one_gen(all_bits >> 5),
-            self.one_gen(all_bits >> 4),
-            self.one_gen(all_bits >> 1),
-            self.one_gen(all_bits),
-        );
-
-        self.new_inode(nw, ne, sw, se)
+    /// Positions of all alive cells, or rather of all cells differing from the background,
+    /// found without visiting empty regions.
+    pub fn alive_cells(&self) -> Vec<Position> {
+        let store = self.store.lock();
+        let root = self.root.unwrap();
+        let mut cells = Vec::new();
+        store.for_each_alive(root, root.node(&*store).level().min_pos(), &mut |pos| {
+            cells.push(pos)
+        });
+        cells
     }
 
-    // to update a cell be have to look at 9 cells (itself and the 8 directly adjecent ones)
-    // so we still have to use a u16 bitmap.
-
-    // the bottom three bits a..=c are the south neighbors
-    // bits e..=g are the current row with 5 being the cell itself
-    // i..=k are the north neighbors
-
-    #[allow(clippy::inconsistent_digit_grouping)]
-    fn one_gen(&mut self, mut bitmask: u16) -> Id {
-        if bitmask == 0 {
-            return self.new_leaf(Cell::Dead);
-        }
-
-        let center = (bitmask >> 5) & 1;
-        bitmask &= 0b00000__111_0101_0111; // mask out bits we don't care about
-        let neighbor_count = bitmask.count_ones();
-        if neighbor_count == 3 || (neighbor_count == 2 && center != 0) {
-            self.new_leaf(Cell::Alive)
-        } else {
-            self.new_leaf(Cell::Dead)
-        }
+    /// Number of cells which aren't dead, or rather which differ from the background.
+    pub fn population(&self) -> u32 {
+        self.root.unwrap().node(&*self.store.lock()).population()
     }
-}
 
-// this can move into another class, when the refactoring of the leaves to Bool8x8 has been done.
-impl Universe {
-    fn centered_horizontal(&mut self, west: Id, east: Id) -> Id {
-        let (west, east) = (west.inode(self), east.inode(self));
-        debug_assert!(west.level == east.level, "levels must be the same");
-
-        let (nw, ne, sw, se) = (
-            west.ne.inode(self).se,
-            east.nw.inode(self).sw,
-            west.se.inode(self).ne,
-            east.sw.inode(self).nw,
-        );
-        self.new_inode(nw, ne, sw, se)
+    /// Number of cells in each state, indexed by the state.
+    /// Cells in the state of the background aren't counted.
+    pub fn census(&self) -> Vec<u64> {
+        let store = self.store.lock();
+        let mut census = vec![0; store.rule().states() as usize];
+        store.census(self.root.unwrap(), &mut census, &mut HashMap::new());
+        // the alive cells of the nodes are the dead ones in front of an alive background
+        if store.rule.background(self.generation) == Cell::ALIVE {
+            census.swap(0, 1);
+        }
+        census
     }
 
-    fn centered_vertical(&mut self, north: Id, south: Id) -> Id {
-        let (north, south) = (north.inode(self), south.inode(self));
-        debug_assert!(north.level == south.level, "levels must be the same");
-
-        let (nw, ne, sw, se) = (
-            north.sw.inode(self).se,
-            north.se.inode(self).sw,
-            south.nw.inode(self).ne,
-            south.ne.inode(self).nw,
-        );
-        self.new_inode(nw, ne, sw, se)
+    /// Number of cells which differ from the background in the rectangle from `min` to `max`,
+    /// both included.
+    pub fn population_in(&self, min: impl Into<Position>, max: impl Into<Position>) -> u32 {
+        let store = self.store.lock();
+        let root = self.root.unwrap();
+        let corner = root.node(&*store).level().min_pos();
+        store.population_in(root, corner, min.into(), max.into())
+    }
+
+    /// Populations of the squares `1 << cell_size_pow2` cells wide which tile the rectangle
+    /// from `min` to `max`, row by row starting at `min`, as for a zoomed out rendering.
+    ///
+    /// The squares on the east and south edges only count the cells within the rectangle.
+    /// With `min` at a multiple of the square size, every square matches a node of the tree,
+    /// whose population is known without looking at its cells. Sizes of 63 and more put the
+    /// whole rectangle into a single square.
+    pub fn density_grid(
+        &self,
+        min: impl Into<Position>,
+        max: impl Into<Position>,
+        cell_size_pow2: u32,
+    ) -> Vec<u32> {
+        let store = self.store.lock();
+        let root = self.root.unwrap();
+        let corner = root.node(&*store).level().min_pos();
+        store.density_grid(root, corner, min.into(), max.into(), cell_size_pow2)
     }
 
-    fn centered_sub(&mut self, node: Id) -> Id {
-        let node = node.inode(self);
+    pub fn evolve(&mut self) {
+        let mut store = self.store.lock_for_evolution(self.generation);
+        let root = self.join_edges(&mut *store);
+        let root = store.expand_for_evolution(root);
 
-        let (nw, ne, sw, se) = (
-            node.nw.inode(self).se,
-            node.ne.inode(self).sw,
-            node.sw.inode(self).ne,
-            node.se.inode(self).nw,
-        );
-        self.new_inode(nw, ne, sw, se)
+        let result = store.evolve_tree(root);
+        self.root = Some(self.clip(&mut *store, result));
+        self.generation += 1;
     }
 
-    fn centered_subsub(&mut self, node: Id) -> Id {
-        let node = node.inode(self);
-        let (nw, ne, sw, se) = (
-            node.nw.inode(self).se.inode(self).se,
-            node.ne.inode(self).sw.inode(self).sw,
-            node.sw.inode(self).ne.inode(self).ne,
-            node.se.inode(self).nw.inode(self).nw,
-        );
-        self.new_inode(nw, ne, sw, se)
+    /// Shrinks the tree to the smallest one around the origin which still holds the pattern.
+    ///
+    /// Evolving does this after every generation, so it is only worth calling after clearing
+    /// far away cells with [`Universe::set_cell`].
+    pub fn compact(&mut self) {
+        let mut store = self.store.lock();
+        self.root = Some(store.shrink(self.root.unwrap()));
     }
-}
-
-// old universe interface
-// TODO: refactor (maybe make this a store module and put this in a "new" universe module)
 
-// Universe API
-impl Universe {
-    pub fn set_cell(&mut self, pos: impl Into<Position>, cell: Cell) {
-        let pos = pos.into();
-
-        loop {
-            let level = self.root.unwrap().node(self).level();
-            if pos.in_bounds(level) {
-                break;
-            }
-            self.expand();
-        }
-
-        self.root = Some(self.set_tree_cell(self.root.unwrap(), pos, cell));
+    /// North west and south east corner of the square the tree covers. All cells outside of
+    /// it are in the background state.
+    pub fn bounds(&self) -> (Position, Position) {
+        let level = self.root.unwrap().node(&*self.store.lock()).level();
+        (level.min_pos(), level.max_pos())
     }
 
-    pub fn get_cell(&self, pos: impl Into<Position>) -> Cell {
-        let pos = pos.into();
+    // the root with the edges of the bounded grid joined, ready for evolution
+    pub(crate) fn join_edges(&self, table: &mut impl Table) -> Id {
         let root = self.root.unwrap();
-        let coord_range = root.node(self).level().coord_range();
-        if coord_range.contains(&pos.x) && coord_range.contains(&pos.y) {
-            self.get_tree_cell(root, pos)
-        } else {
-            Cell::Dead
+        match self.grid {
+            Some(grid) => grid.join_edges(table, root),
+            None => root,
         }
     }
 
-    pub fn evolve(&mut self) {
-        loop {
-            let iroot = self.root.unwrap().inode(self);
-            let (nw_pop, ne_pop, sw_pop, se_pop) = (
-                iroot.nw.node(self).population(),
-                iroot.ne.node(self).population(),
-                iroot.sw.node(self).population(),
-                iroot.se.node(self).population(),
-            );
-
-            let (nw_inner_pop, ne_inner_pop, sw_inner_pop, se_inner_pop) = (
-                iroot
-                    .nw
-                    .inode(self)
-                    .se
-                    .inode(self)
-                    .se
-                    .node(self)
-                    .population(),
-                iroot
-                    .ne
-                    .inode(self)
-                    .sw
-                    .inode(self)
-                    .sw
-                    .node(self)
-                    .population(),
-                iroot
-                    .sw
-                    .inode(self)
-                    .ne
-                    .inode(self)
-                    .ne
-                    .node(self)
-                    .population(),
-                iroot
-                    .se
-                    .inode(self)
-                    .nw
-                    .inode(self)
-                    .nw
-                    .node(self)
-                    .population(),
-            );
-
-            if self.root.unwrap().node(self).level() >= 3
-                && nw_pop == nw_inner_pop
-                && ne_pop == ne_inner_pop
-                && sw_pop == sw_inner_pop
-                && se_pop == se_inner_pop
-            {
-                break;
-            }
-            self.expand();
-        }
-
-        let root = self.root.unwrap();
-
-        self.root = Some(self.evolve_tree(root));
-        self.generation += 1;
+    // the evolved `result` without the cells that left the bounded grid,
+    // shrunk to the pattern
+    pub(crate) fn clip(&self, table: &mut impl Table, result: Id) -> Id {
+        let result = match self.grid {
+            Some(grid) => grid.clip(table, result),
+            None => result,
+        };
+        table.shrink(result)
     }
 }
@@ -0,0 +1,152 @@
+use std::{
+    fmt,
+    sync::{Arc, OnceLock},
+};
+
+use crate::{
+    core::{Cell, Position},
+    node::Node,
+    rule::Rule,
+    table::ReadTable,
+    universe::{relative, Id, Universe},
+};
+
+// the number of nodes in the first segment of a log, each further one is twice as big
+const FIRST_SEGMENT: usize = 1024;
+
+// append-only copy of the nodes of a store, indexed by their ids. a node is added before its
+// id is handed out and never changes afterwards, so views can read the nodes below their
+// root without locking the store.
+pub(crate) struct NodeLog {
+    rule: Rule,
+    // segment `k` holds the nodes from id `FIRST_SEGMENT * (2^k - 1)` on. it is allocated
+    // with its first node and never moves, so adding nodes doesn't disturb readers.
+    segments: [OnceLock<Box<[OnceLock<Node>]>>; 32],
+}
+
+impl NodeLog {
+    pub(crate) fn new(rule: Rule) -> Self {
+        Self {
+            rule,
+            segments: Default::default(),
+        }
+    }
+
+    // the segment of `id` and its index there
+    fn locate(id: Id) -> (usize, usize) {
+        let segment = (id.0 / FIRST_SEGMENT + 1).ilog2() as usize;
+        (segment, id.0 + FIRST_SEGMENT - (FIRST_SEGMENT << segment))
+    }
+
+    pub(crate) fn insert(&self, id: Id, node: Node) {
+        let (segment, index) = Self::locate(id);
+        let nodes = self.segments[segment].get_or_init(|| {
+            (0..FIRST_SEGMENT << segment)
+                .map(|_| OnceLock::new())
+                .collect()
+        });
+        // the ids of a store are only ever handed out once
+        let _ = nodes[index].set(node);
+    }
+
+    fn get(&self, id: Id) -> Node {
+        let (segment, index) = Self::locate(id);
+        *self.segments[segment]
+            .get()
+            .and_then(|nodes| nodes[index].get())
+            .expect("nodes are logged before their ids are handed out")
+    }
+}
+
+/// Immutable snapshot of a single generation of a [`Universe`].
+///
+/// Nodes never change once interned, so the view only remembers the root and reads the
+/// nodes below it from an append-only log which the node store shares with its views.
+/// Taking a view copies nothing and doesn't lock the store; the view can be sent to other
+/// threads and queried there while the universe keeps evolving. Cloning is cheap.
+#[derive(Clone)]
+pub struct UniverseView {
+    log: Arc<NodeLog>,
+    root: Id,
+    generation: usize,
+    background: Cell,
+}
+
+impl fmt::Debug for UniverseView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UniverseView")
+            .field("root", &self.root)
+            .field("generation", &self.generation)
+            .field("background", &self.background)
+            .finish()
+    }
+}
+
+impl ReadTable for UniverseView {
+    fn node(&self, id: Id) -> Node {
+        self.log.get(id)
+    }
+}
+
+impl UniverseView {
+    pub fn get_cell(&self, pos: impl Into<Position>) -> Cell {
+        let pos = pos.into();
+        let root = self.root;
+        let cell = if pos.in_bounds(root.node(self).level()) {
+            self.get_tree_cell(root, pos)
+        } else {
+            Cell::DEAD
+        };
+        relative(cell, self.background)
+    }
+
+    /// Number of alive cells, or rather of cells differing from the background.
+    pub fn population(&self) -> u32 {
+        self.root.node(self).population()
+    }
+
+    /// Number of cells differing from the background in the rectangle from `min` to `max`,
+    /// see [`Universe::population_in`].
+    pub fn population_in(&self, min: impl Into<Position>, max: impl Into<Position>) -> u32 {
+        let root = self.root;
+        let corner = root.node(self).level().min_pos();
+        ReadTable::population_in(self, root, corner, min.into(), max.into())
+    }
+
+    /// Populations of squares tiling the rectangle from `min` to `max`,
+    /// see [`Universe::density_grid`].
+    pub fn density_grid(
+        &self,
+        min: impl Into<Position>,
+        max: impl Into<Position>,
+        cell_size_pow2: u32,
+    ) -> Vec<u32> {
+        let root = self.root;
+        let corner = root.node(self).level().min_pos();
+        ReadTable::density_grid(self, root, corner, min.into(), max.into(), cell_size_pow2)
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    pub fn background(&self) -> Cell {
+        self.background
+    }
+}
+
+impl Universe {
+    /// Takes a snapshot of the current generation.
+    ///
+    /// This neither copies nodes nor waits for the node store, which other universes sharing
+    /// it may be evolving on. Afterwards the view is independent of the universe.
+    pub fn view(&self) -> UniverseView {
+        let log = self.store.log().clone();
+        UniverseView {
+            background: log.rule.background(self.generation),
+            log,
+            root: self.root.unwrap(),
+            generation: self.generation,
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::time::{Duration, Instant};
+
+use lifeash::{Budget, CancelToken, Cell, Interrupted, Position, Universe};
+
+const R_PENTOMINO: [(i64, i64); 5] = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+
+fn universe_with(cells: impl IntoIterator<Item = (i64, i64)>) -> Universe {
+    let mut universe = Universe::new();
+    universe.initialize();
+    for pos in cells {
+        universe.set_cell(pos, Cell::ALIVE);
+    }
+    universe
+}
+
+// a soup big enough that its first generation takes a few thousand results
+fn soup() -> Universe {
+    let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+    universe_with((0..64 * 64).filter_map(move |i| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        if seed & 1 == 1 {
+            Some((i % 64, i / 64))
+        } else {
+            None
+        }
+    }))
+}
+
+fn sorted_cells(universe: &Universe) -> Vec<Position> {
+    let mut cells = universe.alive_cells();
+    cells.sort();
+    cells
+}
+
+#[test]
+fn interrupted_evolutions_resume() {
+    let mut budgeted = universe_with(R_PENTOMINO.iter().copied());
+    let mut reference = universe_with(R_PENTOMINO.iter().copied());
+    let budget = Budget::unlimited().with_max_work(10);
+    let mut interruptions = 0;
+    for generation in 1..=100 {
+        while budgeted.evolve_with(&budget, |_| {}) == Err(Interrupted) {
+            interruptions += 1;
+            assert!(
+                interruptions < 100_000,
+                "the evolution doesn't get anywhere"
+            );
+        }
+        reference.evolve();
+        assert_eq!(budgeted.generation(), generation);
+        assert_eq!(sorted_cells(&budgeted), sorted_cells(&reference));
+    }
+    assert!(interruptions > 0);
+}
+
+#[test]
+fn cancelled_evolutions_stop() {
+    let mut universe = soup();
+    let cells = sorted_cells(&universe);
+    let token = CancelToken::new();
+    token.cancel();
+    let budget = Budget::unlimited().with_cancel(&token);
+    assert_eq!(universe.evolve_with(&budget, |_| {}), Err(Interrupted));
+    assert_eq!(universe.generation(), 0);
+    assert_eq!(sorted_cells(&universe), cells);
+
+    token.reset();
+    assert_eq!(universe.evolve_with(&budget, |_| {}), Ok(()));
+    let mut reference = soup();
+    reference.evolve();
+    assert_eq!(universe.generation(), 1);
+    assert_eq!(sorted_cells(&universe), sorted_cells(&reference));
+}
+
+#[test]
+fn overdue_evolutions_stop() {
+    let mut universe = soup();
+    let cells = sorted_cells(&universe);
+    let budget = Budget::unlimited().with_deadline(Instant::now());
+    assert_eq!(universe.evolve_with(&budget, |_| {}), Err(Interrupted));
+    assert_eq!(universe.generation(), 0);
+    assert_eq!(sorted_cells(&universe), cells);
+
+    let budget = Budget::unlimited().with_timeout(Duration::from_secs(3600));
+    assert_eq!(universe.evolve_with(&budget, |_| {}), Ok(()));
+    assert_eq!(universe.generation(), 1);
+}
+
+#[test]
+fn progress_grows_to_completion() {
+    let mut universe = soup();
+    let mut progress = Vec::new();
+    universe
+        .evolve_with(&Budget::unlimited(), |done| progress.push(done))
+        .unwrap();
+    assert!(progress.len() > 1);
+    assert!(progress.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(progress[0] > 0.0);
+    assert_eq!(progress.last(), Some(&1.0));
+}
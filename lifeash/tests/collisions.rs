@@ -0,0 +1,84 @@
+use lifeash::{CollisionSearch, Offset, Pattern, Rule, Spaceship, Transform};
+
+const GLIDER: [(i64, i64); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+#[test]
+fn glider_hits_block() {
+    let glider = &Spaceship::life()[0];
+    let block = Pattern::new(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+    let search = CollisionSearch::new(Rule::life(), block)
+        .with_incoming(glider, Transform::Identity)
+        .with_delays(0..=0);
+    // gliders far to the side miss and fly on
+    let misses = search.clone().with_shifts(-6..=-6).run().unwrap();
+    let outcome = &misses.collisions[0].outcome;
+    assert!(outcome.settled.is_some());
+    assert_eq!((outcome.count("block"), outcome.emitted.len()), (1, 1));
+    // head on ones destroy the block
+    let hits = search.with_shifts(-2..=2).run().unwrap();
+    assert_eq!(hits.collisions.len(), 5);
+    assert_eq!(hits.annihilations().count(), 5);
+    assert_eq!(hits.to_csv().lines().count(), hits.collisions.len() + 1);
+}
+
+#[test]
+fn two_gliders_collide_head_on() {
+    let glider = &Spaceship::life()[0];
+    let table = CollisionSearch::new(Rule::life(), Pattern::new(&GLIDER))
+        .with_incoming(glider, Transform::Rotate180)
+        .with_shifts(-2..=2)
+        .with_delays(0..=1)
+        .with_max_generations(200)
+        .run()
+        .unwrap();
+    assert!(table.annihilations().next().is_some());
+    assert!(table.producing("beehive").next().is_some());
+    // gliders on the same lanes and timings collide the same way
+    for collision in &table.collisions {
+        let mut same = table
+            .collisions
+            .iter()
+            .filter(|other| other.incoming[0].lane == collision.incoming[0].lane)
+            .filter(|other| other.incoming[0].timing == collision.incoming[0].timing);
+        assert!(same.all(|other| other.outcome.products.len() == collision.outcome.products.len()));
+    }
+}
+
+#[test]
+fn quotes_names_in_csv() {
+    let glider = Spaceship::new(
+        "glider, \"slow\"; small",
+        &Rule::life(),
+        &GLIDER,
+        4,
+        Offset::new(1, 1),
+    );
+    // the glider misses the block and flies on
+    let table = CollisionSearch::new(
+        Rule::life(),
+        Pattern::new(&[(0, 0), (1, 0), (0, 1), (1, 1)]),
+    )
+    .with_incoming(&glider, Transform::Identity)
+    .with_ships(vec![glider.clone()])
+    .with_shifts(8..=8)
+    .with_delays(0..=0)
+    .run()
+    .unwrap();
+    let csv = table.to_csv();
+    let row = csv.lines().nth(1).unwrap();
+    assert!(
+        row.ends_with(",block@0:0,\"\"\"glider, \"\"\"\"slow\"\"\"\"; small(1 1)@8:84\"\"\""),
+        "{}",
+        row
+    );
+}
+
+#[test]
+fn rejects_incoming_ships_of_another_rule() {
+    let glider = &Spaceship::life()[0];
+    // without births, gliders fall apart
+    let result = CollisionSearch::new("B/S23".parse().unwrap(), Pattern::default())
+        .with_incoming(glider, Transform::Identity)
+        .run();
+    assert_eq!(result.unwrap_err().name(), glider.name());
+}
@@ -0,0 +1,76 @@
+// fixtures shared by the tests, each of which only uses some of them
+#![allow(dead_code)]
+
+use lifeash::{Cell, Offset, Pattern, Position, Rule, Transform, Universe};
+
+pub const GLIDER: [(i64, i64); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+pub fn universe_with<P: Into<Position> + Copy>(rule: Rule, cells: &[P]) -> Universe {
+    let mut universe = Universe::with_rule(rule);
+    universe.initialize();
+    for &pos in cells {
+        universe.set_cell(pos, Cell::ALIVE);
+    }
+    universe
+}
+
+// the sorted alive cells of `cells` after `generations` under `rule`
+pub fn evolved(rule: Rule, cells: &[(i64, i64)], generations: usize) -> Vec<Position> {
+    let mut universe = universe_with(rule, cells);
+    for _ in 0..generations {
+        universe.evolve();
+    }
+    let mut alive = universe.alive_cells();
+    alive.sort();
+    alive
+}
+
+// whether `parent` turns into the cells of `target` from `min` to `max` after `generations`
+pub fn evolves_into(
+    parent: &Pattern,
+    target: &Universe,
+    min: Position,
+    max: Position,
+    generations: usize,
+) -> bool {
+    let mut universe = Universe::with_rule(target.rule());
+    universe.initialize();
+    universe.paste(parent, Position::ORIGIN);
+    for _ in 0..generations {
+        universe.evolve();
+    }
+    (min.y..=max.y)
+        .all(|y| (min.x..=max.x).all(|x| universe.get_cell((x, y)) == target.get_cell((x, y))))
+}
+
+// places `pattern` at every position around the cells and checks it and its border directly
+pub fn naive_find(
+    universe: &Universe,
+    pattern: &Pattern,
+    transform: Transform,
+    reach: i64,
+) -> Vec<Position> {
+    let image = pattern.transformed(transform);
+    let (min, max) = image.bounds();
+    let mut found = Vec::new();
+    for y in -reach..reach {
+        for x in -reach..reach {
+            let pos = Position::new(x, y);
+            let matches = (min.dy - 1..=max.dy + 1).all(|dy| {
+                (min.dx - 1..=max.dx + 1).all(|dx| {
+                    let offset = Offset::new(dx, dy);
+                    let expected = image
+                        .cells()
+                        .iter()
+                        .find(|&&(cell_offset, _)| cell_offset == offset)
+                        .map_or(Cell::DEAD, |&(_, cell)| cell);
+                    universe.get_cell(pos + offset) == expected
+                })
+            });
+            if matches {
+                found.push(pos);
+            }
+        }
+    }
+    found
+}
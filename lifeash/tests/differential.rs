@@ -0,0 +1,412 @@
+use lifeash::{Cell, DenseGrid, Engine, Offset, Position, Rule, TiledUniverse, Universe};
+use proptest::prelude::*;
+
+// patterns are placed around the origin, so they cross the quadrant boundaries of the root
+const EXTENT: i64 = 16;
+
+fn pattern() -> impl Strategy<Value = Vec<(i64, i64)>> {
+    prop::collection::vec((-EXTENT..EXTENT, -EXTENT..EXTENT), 0..96)
+}
+
+fn load(engine: &mut impl Engine, pattern: &[(i64, i64)], offset: Offset) {
+    for &pos in pattern {
+        engine.set_cell(Position::from(pos) + offset, Cell::ALIVE);
+    }
+}
+
+fn alive(pattern: Vec<(i64, i64)>) -> Vec<(i64, i64, Cell)> {
+    pattern
+        .into_iter()
+        .map(|(x, y)| (x, y, Cell::ALIVE))
+        .collect()
+}
+
+fn new_universe() -> Universe {
+    let mut universe = Universe::new();
+    universe.initialize();
+    universe
+}
+
+// compares every cell the pattern can have reached with neighbors up to `radius` away
+fn assert_same(
+    engine: &impl Engine,
+    reference: &DenseGrid,
+    offset: Offset,
+    steps: usize,
+    radius: u32,
+) {
+    let reach = EXTENT + (steps as i64 + 2) * i64::from(radius);
+    for y in -reach..reach {
+        for x in -reach..reach {
+            let pos = Position::new(x, y) + offset;
+            assert_eq!(
+                engine.get_cell(pos),
+                reference.get_cell(pos),
+                "cell {:?} differs after {} generations",
+                pos,
+                steps,
+            );
+        }
+    }
+    assert_eq!(engine.population(), reference.population());
+}
+
+// runs `rule` on the `cells` with hashlife and with the reference engine for `steps`
+// generations and compares them, returning both for further checks
+fn assert_rule_matches_reference(
+    rule: &Rule,
+    cells: &[(i64, i64, Cell)],
+    steps: usize,
+) -> (Universe, DenseGrid) {
+    let mut hashlife = Universe::with_rule(rule.clone());
+    hashlife.initialize();
+    let mut reference = DenseGrid::with_rule(rule.clone());
+    for &(x, y, cell) in cells {
+        hashlife.set_cell((x, y), cell);
+        reference.set_cell(Position::new(x, y), cell);
+    }
+
+    for _ in 0..steps {
+        hashlife.evolve();
+        reference.evolve();
+    }
+
+    assert_eq!(hashlife.background(), rule.background(steps));
+    assert_same(
+        &hashlife,
+        &reference,
+        Offset::new(0, 0),
+        steps,
+        rule.radius(),
+    );
+    (hashlife, reference)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn hashlife_matches_reference(pattern in pattern(), steps in 0usize..48) {
+        let (mut hashlife, mut reference) = (new_universe(), DenseGrid::new());
+        load(&mut hashlife, &pattern, Offset::new(0, 0));
+        load(&mut reference, &pattern, Offset::new(0, 0));
+
+        for _ in 0..steps {
+            Engine::evolve(&mut hashlife);
+            reference.evolve();
+        }
+
+        assert_eq!(Engine::generation(&hashlife), steps);
+        assert_same(&hashlife, &reference, Offset::new(0, 0), steps, 1);
+    }
+
+    #[test]
+    fn hashlife_matches_reference_off_center(
+        pattern in pattern(),
+        steps in 0usize..24,
+        dx in -300i64..300,
+        dy in -300i64..300,
+    ) {
+        let offset = Offset::new(dx, dy);
+        let (mut hashlife, mut reference) = (new_universe(), DenseGrid::new());
+        load(&mut hashlife, &pattern, offset);
+        load(&mut reference, &pattern, offset);
+
+        for _ in 0..steps {
+            Engine::evolve(&mut hashlife);
+            reference.evolve();
+        }
+
+        assert_same(&hashlife, &reference, offset, steps, 1);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn b0_matches_reference(
+        pattern in pattern().prop_map(alive),
+        rule in prop::sample::select(vec!["B0123478/S34678", "B03/S23", "B0/S8", "B013-a/S1"]),
+        steps in 0usize..32,
+    ) {
+        assert_rule_matches_reference(&rule.parse().unwrap(), &pattern, steps);
+    }
+
+    #[test]
+    fn non_totalistic_matches_reference(
+        pattern in pattern().prop_map(alive),
+        rule in prop::sample::select(vec![
+            "B2-a/S12", "B3/S2-i34q", "B2n3/S23-q", "B35y/S1e2-ci3-a4i", "B2o/S2m34pH", "B13/S012V",
+        ]),
+        steps in 0usize..32,
+    ) {
+        assert_rule_matches_reference(&rule.parse().unwrap(), &pattern, steps);
+    }
+
+    #[test]
+    fn generations_match_reference(
+        cells in prop::collection::vec((-EXTENT..EXTENT, -EXTENT..EXTENT, 1u8..4), 0..96),
+        rule in prop::sample::select(vec!["B2/S/C3", "345/2/4", "B3/S23/C5"]),
+        steps in 0usize..32,
+    ) {
+        let rule: Rule = rule.parse().unwrap();
+        let cells: Vec<_> = cells
+            .into_iter()
+            .map(|(x, y, state)| (x, y, Cell::new(state % rule.states())))
+            .collect();
+        let (hashlife, reference) = assert_rule_matches_reference(&rule, &cells, steps);
+
+        let mut census = vec![0; rule.states() as usize];
+        let reach = EXTENT + steps as i64 + 2;
+        for y in -reach..reach {
+            for x in -reach..reach {
+                let cell = reference.get_cell(Position::new(x, y));
+                if !cell.is_dead() {
+                    census[cell.state() as usize] += 1;
+                }
+            }
+        }
+        prop_assert_eq!(hashlife.census(), census);
+    }
+
+    #[test]
+    fn margolus_matches_reference(
+        pattern in pattern().prop_map(alive),
+        rule in prop_oneof![
+            prop::sample::select(vec![
+                // the billiard-ball machine, Critters and Tron
+                "MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15",
+                "MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0",
+                "MS,D15;1;2;3;4;5;6;7;8;9;10;11;12;13;14;0",
+            ]).prop_map(|rule| rule.parse::<Rule>().unwrap()),
+            prop::array::uniform16(0u8..16).prop_map(|mut table| {
+                table[0] = 0;
+                Rule::margolus(table)
+            }),
+        ],
+        steps in 0usize..32,
+    ) {
+        assert_rule_matches_reference(&rule, &pattern, steps);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn larger_than_life_matches_reference(
+        pattern in pattern().prop_map(alive),
+        rule in prop::sample::select(vec![
+            "R2,C0,M1,S3..6,B3..4,NM",
+            "R3,C3,M0,S4..9,B5..6,NN",
+            "R2,C0,M0,S2..5,B3..3,NH",
+            "R5,C0,M1,S34..58,B34..45,NM",
+        ]),
+        steps in 0usize..12,
+    ) {
+        assert_rule_matches_reference(&rule.parse().unwrap(), &pattern, steps);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn tiled_matches_reference(
+        pattern in pattern(),
+        steps in 0usize..48,
+        // the tiles are 64 cells wide, these offsets move the pattern across their borders
+        dx in -130i64..130,
+        dy in -130i64..130,
+    ) {
+        let offset = Offset::new(dx, dy);
+        let (mut tiled, mut reference) = (TiledUniverse::new(), DenseGrid::new());
+        load(&mut tiled, &pattern, offset);
+        load(&mut reference, &pattern, offset);
+
+        for _ in 0..steps {
+            tiled.evolve();
+            reference.evolve();
+        }
+
+        assert_same(&tiled, &reference, offset, steps, 1);
+    }
+
+    #[test]
+    fn conversion_between_engines(pattern in pattern(), steps in 0usize..24) {
+        let mut hashlife = new_universe();
+        load(&mut hashlife, &pattern, Offset::new(0, 0));
+
+        let mut tiled = TiledUniverse::from(&hashlife);
+        for _ in 0..steps {
+            tiled.evolve();
+        }
+        let mut back = Universe::from(&tiled);
+        for _ in 0..steps {
+            back.evolve();
+            hashlife.evolve();
+            hashlife.evolve();
+        }
+
+        prop_assert_eq!(back.generation(), hashlife.generation());
+        let (mut cells, mut expected) = (back.alive_cells(), hashlife.alive_cells());
+        cells.sort();
+        expected.sort();
+        prop_assert_eq!(cells, expected);
+    }
+}
+
+#[cfg(feature = "parallel")]
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn parallel_matches_serial(pattern in pattern(), steps in 0usize..16) {
+        let (mut serial, mut parallel) = (new_universe(), new_universe());
+        // far apart, so the root gets big enough to fork
+        for &offset in &[Offset::new(-400, -400), Offset::new(400, 400)] {
+            load(&mut serial, &pattern, offset);
+            load(&mut parallel, &pattern, offset);
+        }
+
+        for _ in 0..steps {
+            serial.evolve();
+            parallel.par_evolve();
+        }
+
+        for &(x, y) in &[(-400, -400), (400, 400)] {
+            let reach = EXTENT + steps as i64 + 2;
+            for y in y - reach..y + reach {
+                for x in x - reach..x + reach {
+                    prop_assert_eq!(serial.get_cell((x, y)), parallel.get_cell((x, y)));
+                }
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn region_queries_match_cells(
+        pattern in pattern(),
+        steps in 0usize..8,
+        min in (-40i64..40, -40i64..40),
+        (width, height) in (0i64..64, 0i64..64),
+        shift in 0u32..5,
+    ) {
+        let mut universe = new_universe();
+        load(&mut universe, &pattern, Offset::new(0, 0));
+        for _ in 0..steps {
+            universe.evolve();
+        }
+
+        let min = Position::from(min);
+        let max = min + Offset::new(width - 1, height - 1);
+        let (columns, rows) = ((width + (1 << shift) - 1) >> shift, (height + (1 << shift) - 1) >> shift);
+        let mut density = vec![0; (columns * rows) as usize];
+        for y in 0..height {
+            for x in 0..width {
+                if universe.get_cell(min + Offset::new(x, y)) == Cell::ALIVE {
+                    density[((y >> shift) * columns + (x >> shift)) as usize] += 1;
+                }
+            }
+        }
+
+        let population: u32 = density.iter().sum();
+        prop_assert_eq!(universe.population_in(min, max), population);
+        prop_assert_eq!(universe.density_grid(min, max, shift), density.clone());
+        let view = universe.view();
+        prop_assert_eq!(view.population_in(min, max), population);
+        prop_assert_eq!(view.density_grid(min, max, shift), density);
+        let whole = if width > 0 && height > 0 { vec![population] } else { Vec::new() };
+        prop_assert_eq!(universe.density_grid(min, max, 63 + shift * 16), whole.clone());
+        prop_assert_eq!(view.density_grid(min, max, u32::MAX), whole);
+    }
+}
+
+#[test]
+fn offset_arithmetic() {
+    let (a, b) = (Offset::new(1, 2), Offset::new(10, 20));
+    assert_eq!(a + b, Offset::new(11, 22));
+    assert_eq!(b - a, Offset::new(9, 18));
+
+    let mut c = a;
+    c += b;
+    c -= Offset::new(0, 1);
+    assert_eq!(c, Offset::new(11, 21));
+}
+
+#[test]
+fn blinker_oscillates() {
+    let mut universe = new_universe();
+    for &x in &[-1, 0, 1] {
+        universe.set_cell((x, 0), Cell::ALIVE);
+    }
+
+    universe.evolve();
+    for &y in &[-1, 0, 1] {
+        assert_eq!(universe.get_cell((0, y)), Cell::ALIVE);
+    }
+    assert_eq!(universe.get_cell((-1, 0)), Cell::DEAD);
+
+    universe.evolve();
+    assert_eq!(universe.get_cell((-1, 0)), Cell::ALIVE);
+    assert_eq!(universe.population(), 3);
+}
+
+#[test]
+fn block_is_still() {
+    let mut universe = new_universe();
+    for &pos in &[(-1, -1), (0, -1), (-1, 0), (0, 0)] {
+        universe.set_cell(pos, Cell::ALIVE);
+    }
+
+    for _ in 0..4 {
+        universe.evolve();
+        assert_eq!(universe.population(), 4);
+    }
+}
+
+#[test]
+fn root_shrinks_around_remaining_pattern() {
+    let mut universe = new_universe();
+    let small = universe.bounds();
+    for &pos in &[(-1, -1), (0, -1), (-1, 0), (0, 0)] {
+        universe.set_cell(pos, Cell::ALIVE);
+    }
+    // a lone cell far away dies right away
+    universe.set_cell((1000, -1000), Cell::ALIVE);
+    assert!(universe.bounds().1.x >= 1000);
+
+    universe.evolve();
+    assert_eq!(universe.population(), 4);
+    assert_eq!(universe.bounds(), small);
+
+    // a blinker sticking out of the small tree keeps a bigger one
+    for &x in &[5, 6, 7] {
+        universe.set_cell((x, 0), Cell::ALIVE);
+    }
+    universe.evolve();
+    let (min, max) = universe.bounds();
+    assert!(min.x <= 6 && 6 <= max.x && min.y <= -1 && 1 <= max.y);
+    assert_eq!(universe.get_cell((6, -1)), Cell::ALIVE);
+
+    universe.set_cell((-300, 40), Cell::ALIVE);
+    universe.set_cell((-300, 40), Cell::DEAD);
+    assert!(universe.bounds().0.x <= -300);
+    universe.compact();
+    assert_eq!(universe.bounds(), (min, max));
+    assert_eq!(universe.population(), 7);
+}
+
+#[test]
+#[should_panic(expected = "infinite plane")]
+fn tiled_engine_rejects_bounded_grids() {
+    let mut torus = Universe::with_grid(Rule::life(), "T16,16".parse().unwrap());
+    torus.initialize();
+    torus.set_cell((0, 0), Cell::ALIVE);
+    let _ = TiledUniverse::from(&torus);
+}
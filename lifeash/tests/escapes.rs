@@ -0,0 +1,147 @@
+use lifeash::{Cell, Offset, Position, Rule, Spaceship, Transform, Universe};
+
+const GOSPER_GUN: &str = "
+........................O...........
+......................O.O...........
+............OO......OO............OO
+...........O...O....OO............OO
+OO........O.....O...OO..............
+OO........O...O.OO....O.O...........
+..........O.....O.......O...........
+...........O...O....................
+............OO......................
+";
+
+fn universe_from(picture: &str, origin: Position) -> Universe {
+    let mut universe = Universe::new();
+    universe.initialize();
+    for (y, line) in picture.trim().lines().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            if c == 'O' {
+                universe.set_cell(origin + Offset::new(x as i64, y as i64), Cell::ALIVE);
+            }
+        }
+    }
+    universe
+}
+
+#[test]
+fn gosper_gun_emits_a_glider_stream() {
+    let universe = universe_from(GOSPER_GUN, Position::ORIGIN);
+    let report = universe.escapes(
+        &Spaceship::life(),
+        Position::new(-2, -2),
+        Position::new(38, 14),
+        300,
+    );
+    assert!(report.escapes.len() >= 8, "{}", report);
+    let first = &report.escapes[0];
+    for (i, escape) in report.escapes.iter().enumerate() {
+        assert_eq!(escape.ship, "glider");
+        assert_eq!(escape.velocity, Offset::new(1, 1));
+        assert_eq!(escape.period, 4);
+        assert_eq!(escape.lane, first.lane);
+        assert_eq!(escape.timing, first.timing + 30 * i as i64);
+        assert_eq!(escape.generation, first.generation + 30 * i);
+    }
+    // the universe itself was left alone
+    assert_eq!(universe.generation(), 0);
+}
+
+#[test]
+fn classifies_spaceships_by_heading() {
+    let mut universe = Universe::new();
+    universe.initialize();
+    let ships = Spaceship::life();
+    let lwss = [
+        (1, 0),
+        (4, 0),
+        (0, 1),
+        (0, 2),
+        (4, 2),
+        (0, 3),
+        (1, 3),
+        (2, 3),
+        (3, 3),
+    ];
+    let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    // an LWSS heading east and a glider heading north west, both already outside
+    for &offset in &lwss {
+        let pos = Position::new(40, 5) + Transform::FlipHorizontal.apply(offset.into());
+        universe.set_cell(pos, Cell::ALIVE);
+    }
+    for &offset in &glider {
+        let pos = Position::new(-20, -20) + Transform::Rotate180.apply(offset.into());
+        universe.set_cell(pos, Cell::ALIVE);
+    }
+    // a block stays inside
+    for &pos in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+        universe.set_cell(pos, Cell::ALIVE);
+    }
+
+    let report = universe.escapes(&ships, Position::new(-5, -5), Position::new(5, 5), 8);
+    let mut found: Vec<_> = report
+        .escapes
+        .iter()
+        .map(|escape| (escape.ship.as_str(), escape.velocity, escape.generation))
+        .collect();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            ("LWSS", Offset::new(2, 0), 0),
+            ("glider", Offset::new(-1, -1), 0)
+        ]
+    );
+    let lane = |universe: &Universe| {
+        let report = universe.escapes(&ships, Position::new(-5, -5), Position::new(5, 5), 0);
+        report
+            .escapes
+            .iter()
+            .find(|e| e.ship == "LWSS")
+            .unwrap()
+            .lane
+    };
+    // moving the ship three rows south moves it three lanes to its right
+    let mut south = Universe::new();
+    south.initialize();
+    for pos in universe.alive_cells() {
+        if pos.x > 20 {
+            south.set_cell(pos + Offset::new(0, 3), Cell::ALIVE);
+        }
+    }
+    assert_eq!(lane(&south), lane(&universe) - 3);
+    assert_eq!(report.to_string().lines().count(), 2);
+}
+
+#[test]
+fn spaceship_follows_its_lane_and_timing() {
+    let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+    let ships = Spaceship::life();
+    // the same glider seen in every phase reports the same lane and timing
+    let mut universe = Universe::new();
+    universe.initialize();
+    for &pos in &glider {
+        universe.set_cell(Position::new(10, 10) + Offset::from(pos), Cell::ALIVE);
+    }
+    let mut seen = Vec::new();
+    for _ in 0..8 {
+        let report = universe.escapes(&ships, Position::new(-5, -5), Position::new(5, 5), 0);
+        let escape = &report.escapes[0];
+        seen.push((escape.lane, escape.timing));
+        universe.evolve();
+    }
+    assert!(seen.iter().all(|&s| s == seen[0]), "{:?}", seen);
+}
+
+#[test]
+#[should_panic(expected = "don't move")]
+fn rejects_wrong_displacement() {
+    Spaceship::new(
+        "glider",
+        &Rule::life(),
+        &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+        4,
+        Offset::new(1, -1),
+    );
+}
@@ -0,0 +1,318 @@
+use lifeash::{BoundedGrid, Cell, Position, Rule, SharedStore, Topology, Universe};
+use proptest::prelude::*;
+
+const GLIDER: [(i64, i64); 5] = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)];
+
+fn topology() -> impl Strategy<Value = Topology> {
+    prop::sample::select(vec![
+        Topology::Plane,
+        Topology::Torus,
+        Topology::KleinBottle {
+            horizontal_twist: true,
+        },
+        Topology::KleinBottle {
+            horizontal_twist: false,
+        },
+        Topology::CrossSurface,
+        Topology::Sphere,
+    ])
+}
+
+// Life on a grid of rows, with the neighbors across the edges spelled out per topology
+struct Naive {
+    grid: BoundedGrid,
+    cells: Vec<Vec<bool>>,
+}
+
+impl Naive {
+    fn new(grid: BoundedGrid) -> Self {
+        let cells = vec![vec![false; grid.width as usize]; grid.height as usize];
+        Self { grid, cells }
+    }
+
+    fn get(&self, x: i64, y: i64) -> bool {
+        let (w, h) = (i64::from(self.grid.width), i64::from(self.grid.height));
+        let (outside_x, outside_y) = (!(0..w).contains(&x), !(0..h).contains(&y));
+        let (x, y) = match self.grid.topology {
+            Topology::Plane if outside_x || outside_y => return false,
+            Topology::Sphere if outside_x && outside_y => match (x, y) {
+                (-1, -1) => (0, 0),
+                _ if (x, y) == (w, h) => (w - 1, h - 1),
+                _ => return false,
+            },
+            Topology::Sphere if x == -1 => (y, 0),
+            Topology::Sphere if x == w => (y, h - 1),
+            Topology::Sphere if y == -1 => (0, x),
+            Topology::Sphere if y == h => (w - 1, x),
+            topology => {
+                let (twist_columns, twist_rows) = match topology {
+                    Topology::KleinBottle { horizontal_twist } => {
+                        (!horizontal_twist, horizontal_twist)
+                    }
+                    Topology::CrossSurface => (true, true),
+                    _ => (false, false),
+                };
+                let mut y = if outside_x && twist_columns {
+                    h - 1 - y
+                } else {
+                    y
+                };
+                let mut x = x.rem_euclid(w);
+                if !(0..h).contains(&y) {
+                    y = y.rem_euclid(h);
+                    if twist_rows {
+                        x = w - 1 - x;
+                    }
+                }
+                (x, y)
+            }
+        };
+        self.cells[y as usize][x as usize]
+    }
+
+    fn evolve(&mut self) {
+        let life = Rule::life();
+        let mut next = self.cells.clone();
+        for (y, row) in next.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                let (x, y) = (x as i64, y as i64);
+                // the neighborhood as a mask from north west to south east
+                let mut neighbors = 0;
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        if (dx, dy) != (0, 0) {
+                            neighbors = neighbors << 1 | self.get(x + dx, y + dy) as u8;
+                        }
+                    }
+                }
+                *cell = if self.get(x, y) {
+                    life.survives(neighbors)
+                } else {
+                    life.is_born(neighbors)
+                };
+            }
+        }
+        self.cells = next;
+    }
+}
+
+fn glider_on(grid: BoundedGrid) -> Universe {
+    let mut universe = Universe::with_grid(Rule::life(), grid);
+    universe.initialize();
+    for &pos in &GLIDER {
+        universe.set_cell(pos, Cell::ALIVE);
+    }
+    universe
+}
+
+#[test]
+fn glider_circles_torus() {
+    let grid = BoundedGrid::new(Topology::Torus, 12, 12);
+    let mut universe = glider_on(grid);
+    let mut start = universe.alive_cells();
+    start.sort();
+
+    for generation in 1..=4 * 12 {
+        universe.evolve();
+        assert_eq!(universe.population(), 5, "generation {}", generation);
+    }
+    let mut cells = universe.alive_cells();
+    cells.sort();
+    assert_eq!(cells, start);
+}
+
+#[test]
+fn large_grids_share_stores() {
+    let store = SharedStore::with_rule(Rule::life());
+    let mut plane = Universe::with_store(&store);
+    plane.initialize();
+    // a glider crossing the corner of a grid wider than the border is copied at once
+    let grid: BoundedGrid = "T300,200".parse().unwrap();
+    let (min, max) = grid.corners();
+    let mut torus = Universe::with_store_and_grid(&store, grid);
+    assert!(torus.store().ptr_eq(&store));
+    torus.initialize();
+    for &(x, y) in &GLIDER {
+        plane.set_cell((max.x - 4 + x, max.y - 4 + y), Cell::ALIVE);
+        torus.set_cell((max.x - 4 + x, max.y - 4 + y), Cell::ALIVE);
+    }
+
+    for _ in 0..40 {
+        plane.evolve();
+        torus.evolve();
+    }
+    let wrap = |pos: Position| {
+        let x = (pos.x - min.x).rem_euclid(300) + min.x;
+        let y = (pos.y - min.y).rem_euclid(200) + min.y;
+        Position::new(x, y)
+    };
+    let mut expected: Vec<Position> = plane.alive_cells().into_iter().map(wrap).collect();
+    expected.sort();
+    let mut cells = torus.alive_cells();
+    cells.sort();
+    assert_eq!(cells, expected);
+}
+
+#[test]
+#[should_panic(expected = "B0")]
+fn shared_stores_with_b0_rules_are_rejected() {
+    let store = SharedStore::with_rule("B03/S23".parse().unwrap());
+    Universe::with_store_and_grid(&store, "T16,16".parse().unwrap());
+}
+
+#[test]
+fn glider_crashes_into_edge_of_plane() {
+    let mut universe = glider_on(BoundedGrid::new(Topology::Plane, 12, 12));
+    for _ in 0..64 {
+        universe.evolve();
+    }
+    // a glider hitting a corner ends as a block
+    assert_eq!(universe.population(), 4);
+    assert_eq!(universe.get_cell((5, 5)), Cell::ALIVE);
+}
+
+#[test]
+fn cells_outside_of_grid_are_ignored() {
+    let mut universe = Universe::with_grid(Rule::life(), "T8,4".parse().unwrap());
+    universe.initialize();
+    universe.set_cell((4, 0), Cell::ALIVE);
+    universe.set_cell((0, -3), Cell::ALIVE);
+    assert_eq!(universe.population(), 0);
+    assert_eq!(universe.get_cell((4, 0)), Cell::DEAD);
+
+    // a blinker across the left and right edges
+    for &x in &[-4, 3, 2] {
+        universe.set_cell((x, 0), Cell::ALIVE);
+    }
+    universe.evolve();
+    assert_eq!(universe.get_cell((3, -1)), Cell::ALIVE);
+    assert_eq!(universe.get_cell((3, 1)), Cell::ALIVE);
+    assert_eq!(universe.population(), 3);
+}
+
+#[test]
+fn torus_wraps_larger_than_life() {
+    let rule: Rule = "R2,C0,M1,S3..6,B3..4,NM".parse().unwrap();
+    let grid: BoundedGrid = "T16,12".parse().unwrap();
+    let pattern = [(0, 0), (1, 0), (2, 1), (0, 2), (-1, 1), (1, 3)];
+    // the same pattern, once in the middle and once split across the corners
+    let shifts = [(0, 0), (8, 6)];
+    let mut universes: Vec<Universe> = shifts
+        .iter()
+        .map(|&(dx, dy)| {
+            let mut universe = Universe::with_grid(rule.clone(), grid);
+            universe.initialize();
+            for &(x, y) in &pattern {
+                let x = (x + dx + 8) % 16 - 8;
+                let y = (y + dy + 6) % 12 - 6;
+                universe.set_cell((x, y), Cell::ALIVE);
+            }
+            universe
+        })
+        .collect();
+
+    for _ in 0..16 {
+        for universe in &mut universes {
+            universe.evolve();
+        }
+        assert!(universes[0].population() > 0);
+        for y in -6..6 {
+            for x in -8..8 {
+                let (shifted_x, shifted_y) = ((x + 16) % 16 - 8, (y + 12) % 12 - 6);
+                assert_eq!(
+                    universes[0].get_cell((x, y)),
+                    universes[1].get_cell((shifted_x, shifted_y))
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn billiard_ball_circles_torus() {
+    let rule: Rule = "MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15".parse().unwrap();
+    let mut universe = Universe::with_grid(rule, "T8,6".parse().unwrap());
+    universe.initialize();
+    universe.set_cell((0, 0), Cell::ALIVE);
+    for _ in 0..24 {
+        universe.evolve();
+        assert_eq!(universe.population(), 1);
+    }
+    assert_eq!(universe.get_cell((0, 0)), Cell::ALIVE);
+}
+
+#[test]
+fn parses_golly_notation() {
+    for &(grid, expected) in &[
+        ("T64,32", "T64,32"),
+        ("p10", "P10,10"),
+        ("K64*,32", "K64*,32"),
+        ("K64,32*", "K64,32*"),
+        ("C5,7", "C5,7"),
+        ("S16", "S16"),
+        ("S16,16", "S16"),
+    ] {
+        assert_eq!(grid.parse::<BoundedGrid>().unwrap().to_string(), expected);
+    }
+    assert_eq!(
+        "K3*,4".parse::<BoundedGrid>().unwrap().topology,
+        Topology::KleinBottle {
+            horizontal_twist: true
+        }
+    );
+
+    for grid in &[
+        "", "X4,4", "T0,4", "T4,", "K4,4", "K4*,4*", "T4*,4", "S4,5", "T-1,3",
+    ] {
+        assert!(
+            grid.parse::<BoundedGrid>().is_err(),
+            "{} was accepted",
+            grid
+        );
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn bounded_grids_match_naive(
+        topology in topology(),
+        width in 3u32..12,
+        height in 3u32..12,
+        pattern in prop::collection::vec((0i64..12, 0i64..12), 0..64),
+        steps in 0usize..24,
+    ) {
+        let height = if topology == Topology::Sphere { width } else { height };
+        let grid = BoundedGrid::new(topology, width, height);
+        let (min, _) = grid.corners();
+        let mut hashlife = Universe::with_grid(Rule::life(), grid);
+        hashlife.initialize();
+        let mut naive = Naive::new(grid);
+        for &(x, y) in &pattern {
+            let (x, y) = (x % i64::from(width), y % i64::from(height));
+            hashlife.set_cell(Position::new(min.x + x, min.y + y), Cell::ALIVE);
+            naive.cells[y as usize][x as usize] = true;
+        }
+
+        for _ in 0..steps {
+            hashlife.evolve();
+            naive.evolve();
+        }
+
+        let mut population = 0;
+        for y in -1..=i64::from(height) {
+            for x in -1..=i64::from(width) {
+                let inside = (0..i64::from(width)).contains(&x) && (0..i64::from(height)).contains(&y);
+                let alive = inside && naive.cells[y as usize][x as usize];
+                population += alive as u32;
+                prop_assert_eq!(
+                    hashlife.get_cell(Position::new(min.x + x, min.y + y)),
+                    if alive { Cell::ALIVE } else { Cell::DEAD },
+                    "cell ({}, {}) of {} differs after {} generations", x, y, grid, steps,
+                );
+            }
+        }
+        prop_assert_eq!(hashlife.population(), population);
+    }
+}
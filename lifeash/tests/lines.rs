@@ -0,0 +1,124 @@
+use std::convert::TryFrom;
+
+use lifeash::{Cell, LineRule, LineUniverse};
+use proptest::prelude::*;
+
+// evolves `cells` directly, growing them by a cell on both sides every generation, together
+// with the background around them
+fn naive(rule: &LineRule, cells: &[Cell], steps: usize) -> (Vec<Cell>, Cell) {
+    let (mut cells, mut background) = (cells.to_vec(), Cell::DEAD);
+    for _ in 0..steps {
+        let get = |i: i64| {
+            usize::try_from(i)
+                .ok()
+                .and_then(|i| cells.get(i).copied())
+                .unwrap_or(background)
+        };
+        cells = (-1..cells.len() as i64 + 1)
+            .map(|i| rule.next(get(i - 1), get(i), get(i + 1)))
+            .collect();
+        background = rule.next(background, background, background);
+    }
+    (cells, background)
+}
+
+fn line_rule() -> impl Strategy<Value = LineRule> {
+    prop_oneof![
+        any::<u8>().prop_map(LineRule::elementary),
+        (0u128..2187).prop_map(|code| LineRule::totalistic(code, 3)),
+        (0u128..1 << 20).prop_map(|code| LineRule::totalistic(code, 4)),
+    ]
+}
+
+#[test]
+fn rule_90_draws_sierpinski_triangle() {
+    let mut line = LineUniverse::new(LineRule::elementary(90));
+    line.set_cell(0, Cell::ALIVE);
+    for generation in 1..=100u32 {
+        line.evolve();
+        assert_eq!(line.population(), 1 << generation.count_ones());
+    }
+    assert_eq!(line.generation(), 100);
+    let cells = line.alive_cells();
+    assert_eq!(cells[0], -100);
+    assert_eq!(cells[cells.len() - 1], 100);
+}
+
+#[test]
+fn rule_110_matches_known_row() {
+    let mut line = LineUniverse::new(LineRule::elementary(110));
+    line.set_cell(0, Cell::ALIVE);
+    for _ in 0..4 {
+        line.evolve();
+    }
+    // rule 110 grows to the left
+    assert_eq!(line.alive_cells(), vec![-4, -3, -2, -1, 0]);
+    line.evolve();
+    assert_eq!(line.alive_cells(), vec![-5, -4, 0]);
+}
+
+#[test]
+fn renders_spacetime_diagram() {
+    let mut line = LineUniverse::new(LineRule::totalistic(6, 3));
+    line.set_cell(0, Cell::new(2));
+    let mut expected = line.clone();
+
+    let universe = line.spacetime(8);
+    assert_eq!(line.generation(), 8);
+    assert_eq!(universe.rule().states(), 3);
+    for y in 0..=8 {
+        for x in -10..=10 {
+            assert_eq!(universe.get_cell((x, y)), expected.get_cell(x));
+        }
+        expected.evolve();
+    }
+}
+
+#[test]
+fn rule_1_alternates_the_background() {
+    let mut line = LineUniverse::new(LineRule::elementary(1));
+    line.set_cell(0, Cell::ALIVE);
+    line.evolve();
+    // only the neighborhood of the cell stays dead
+    assert_eq!(line.background(), Cell::ALIVE);
+    assert_eq!(line.alive_cells(), vec![-1, 0, 1]);
+    assert_eq!(line.get_cell(0), Cell::DEAD);
+    assert_eq!(line.get_cell(1000), Cell::ALIVE);
+    line.evolve();
+    assert_eq!(line.background(), Cell::DEAD);
+    assert_eq!(line.alive_cells(), vec![0]);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn line_matches_naive(
+        rule in line_rule(),
+        pattern in prop::collection::vec(0u8..4, 0..40),
+        min in -30i64..30,
+        steps in 0usize..40,
+    ) {
+        let pattern: Vec<Cell> = pattern
+            .into_iter()
+            .map(|state| Cell::new(state % rule.colors()))
+            .collect();
+        let mut line = LineUniverse::new(rule.clone());
+        for (i, &cell) in pattern.iter().enumerate() {
+            line.set_cell(min + i as i64, cell);
+        }
+        for _ in 0..steps {
+            line.evolve();
+        }
+
+        let (expected, background) = naive(&rule, &pattern, steps);
+        prop_assert_eq!(line.background(), background);
+        let start = min - steps as i64;
+        for (i, &cell) in expected.iter().enumerate() {
+            prop_assert_eq!(line.get_cell(start + i as i64), cell);
+        }
+        prop_assert_eq!(line.get_cell(start - 1), background);
+        let population = expected.iter().filter(|&&cell| cell != background).count();
+        prop_assert_eq!(line.population() as usize, population);
+    }
+}
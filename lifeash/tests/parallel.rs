@@ -0,0 +1,71 @@
+#![cfg(feature = "parallel")]
+
+use lifeash::{Cell, Rule, Universe};
+
+// a soup in two corners far apart, so the root gets big enough to fork
+fn soup(rule: &Rule) -> Universe {
+    let mut universe = Universe::with_rule(rule.clone());
+    universe.initialize();
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    for &(cx, cy) in &[(-300, -300), (300, 300)] {
+        for y in 0..16 {
+            for x in 0..16 {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                let state = (seed % u64::from(rule.states()) * (seed >> 32 & 1)) as u8;
+                universe.set_cell((cx + x, cy + y), Cell::new(state));
+            }
+        }
+    }
+    universe
+}
+
+fn sorted_cells(universe: &Universe) -> Vec<(i64, i64, u8)> {
+    let mut cells: Vec<_> = universe
+        .alive_cells()
+        .into_iter()
+        .map(|pos| (pos.x, pos.y, universe.get_cell(pos).state()))
+        .collect();
+    cells.sort_unstable();
+    cells
+}
+
+#[test]
+fn par_evolve_matches_evolve() {
+    for rule in &[
+        "B3/S23",
+        "B2/S/C3",
+        "B0123478/S34678",
+        "MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15",
+    ] {
+        let rule: Rule = rule.parse().unwrap();
+        let (mut serial, mut parallel) = (soup(&rule), soup(&rule));
+        // the same store alternates between both, so their memoized results are mixed
+        let mut mixed = Universe::with_store(parallel.store());
+        mixed.initialize();
+        for pos in serial.alive_cells() {
+            mixed.set_cell(pos, serial.get_cell(pos));
+        }
+        for generation in 1..=40 {
+            serial.evolve();
+            parallel.par_evolve();
+            if generation % 2 == 0 {
+                mixed.evolve();
+            } else {
+                mixed.par_evolve();
+            }
+            let expected = sorted_cells(&serial);
+            assert_eq!(
+                sorted_cells(&parallel),
+                expected,
+                "{} at {}",
+                rule,
+                generation
+            );
+            assert_eq!(sorted_cells(&mixed), expected, "{} at {}", rule, generation);
+            assert_eq!(parallel.background(), serial.background());
+            assert_eq!(parallel.generation(), generation);
+        }
+    }
+}
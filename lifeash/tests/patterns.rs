@@ -0,0 +1,168 @@
+use lifeash::{Cell, Offset, Pattern, Position, Rule, Transform};
+use proptest::prelude::*;
+
+mod common;
+
+use common::{naive_find, universe_with, GLIDER};
+
+#[test]
+fn finds_gliders_and_their_headings() {
+    let glider = Pattern::new(&GLIDER);
+    let mut cells = Vec::new();
+    let mut expected = Vec::new();
+    for (i, &transform) in Transform::ALL.iter().enumerate() {
+        let origin = Position::new(12 * i as i64 - 40, 7 * i as i64 - 30);
+        for &offset in &GLIDER {
+            cells.push(origin + transform.apply(offset.into()));
+        }
+        expected.push((origin, transform));
+    }
+    expected.sort_by_key(|&(pos, _)| pos);
+    let mut universe = universe_with(Rule::life(), &cells);
+    assert_eq!(universe.find(&glider, &Transform::ALL), expected);
+    assert_eq!(
+        universe.find(&glider, &[Transform::Rotate180]).len(),
+        1,
+        "only the glider heading north west"
+    );
+
+    // after a period every glider moved a cell diagonally in its own direction
+    for _ in 0..4 {
+        universe.evolve();
+    }
+    let mut moved: Vec<_> = expected
+        .iter()
+        .map(|&(pos, transform)| (pos + transform.apply(Offset::new(1, 1)), transform))
+        .collect();
+    moved.sort_by_key(|&(pos, _)| pos);
+    assert_eq!(universe.find(&glider, &Transform::ALL), moved);
+}
+
+#[test]
+fn only_finds_isolated_matches() {
+    let block = Pattern::new(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+    let square = [(0, 0), (1, 0), (0, 1), (1, 1)];
+    let mut cells: Vec<Position> = square.iter().map(|&pos| pos.into()).collect();
+    // a block with a cell two columns away
+    cells.push(Position::new(3, 0));
+    // and one touching a cell at its corner
+    cells.extend(square.iter().map(|&(x, y)| Position::new(x + 20, y)));
+    cells.push(Position::new(22, 2));
+
+    let universe = universe_with(Rule::life(), &cells);
+    // the block looks the same in every orientation, so it is reported once
+    assert_eq!(
+        universe.find(&block, &Transform::ALL),
+        vec![(Position::new(0, 0), Transform::Identity)]
+    );
+}
+
+#[test]
+fn finds_matches_across_blocks_of_a_sparse_universe() {
+    let glider = Pattern::new(&GLIDER);
+    // gliders straddling the borders between blocks of the tree, far apart from each other
+    let origins = [
+        Position::new(-2, -2),
+        Position::new(15, 31),
+        Position::new(-33, 62),
+        Position::new(4095, -4097),
+        Position::new(-70000, 90001),
+    ];
+    let mut cells: Vec<Position> = origins
+        .iter()
+        .flat_map(|&origin| GLIDER.iter().map(move |&offset| origin + offset.into()))
+        .collect();
+    // a lone cell too small to hold a glider
+    cells.push(Position::new(500, 500));
+    let universe = universe_with(Rule::life(), &cells);
+
+    let mut expected: Vec<_> = origins
+        .iter()
+        .map(|&origin| (origin, Transform::Identity))
+        .collect();
+    expected.sort_by_key(|&(pos, _)| pos);
+    assert_eq!(universe.find(&glider, &Transform::ALL), expected);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn find_matches_naive(
+        pattern in prop::collection::vec((0i64..3, 0i64..3), 1..5),
+        cells in prop::collection::vec((-8i64..8, -8i64..8), 0..48),
+        transforms in prop::sample::subsequence(Transform::ALL.to_vec(), 1..=8),
+    ) {
+        let pattern = Pattern::new(&pattern);
+        let cells: Vec<Position> = cells.into_iter().map(Position::from).collect();
+        let universe = universe_with(Rule::life(), &cells);
+
+        let found = universe.find(&pattern, &transforms);
+        let mut seen = Vec::new();
+        for &transform in &transforms {
+            for pos in naive_find(&universe, &pattern, transform, 16) {
+                // the same cells in an earlier orientation count as the same match
+                let mut key: Vec<Position> = pattern
+                    .cells()
+                    .iter()
+                    .map(|&(offset, _)| pos + transform.apply(offset))
+                    .collect();
+                key.sort();
+                if seen.contains(&key) {
+                    continue;
+                }
+                seen.push(key);
+                prop_assert!(found.contains(&(pos, transform)), "{:?} {:?} is missing", pos, transform);
+            }
+        }
+        prop_assert_eq!(found.len(), seen.len());
+    }
+
+    #[test]
+    fn digests_are_invariant(
+        cells in prop::collection::vec((-8i64..8, -8i64..8), 1..24),
+        shift in (-100i64..100, -100i64..100),
+        transform in prop::sample::select(Transform::ALL.to_vec()),
+    ) {
+        let pattern = Pattern::new(&cells);
+        let moved: Vec<_> = cells.iter().map(|&(x, y)| (x + shift.0, y + shift.1)).collect();
+        prop_assert_eq!(Pattern::new(&moved).digest(), pattern.digest());
+        let image = pattern.transformed(transform);
+        prop_assert_eq!(image.canonical_digest(), pattern.canonical_digest());
+        prop_assert_eq!(image.canonical(), pattern.canonical());
+    }
+}
+
+#[test]
+fn digests_ignore_placement() {
+    let mut universe = universe_with::<Position>(Rule::life(), &[]);
+    for &offset in &GLIDER {
+        universe.set_cell(Position::new(-30, 12) + offset.into(), Cell::ALIVE);
+    }
+    let glider = Pattern::new(&GLIDER);
+    assert_eq!(universe.to_pattern().digest(), glider.digest());
+    // a fixed value, so digests written to disk stay valid
+    assert_eq!(glider.digest(), 0x820b_a35a_0736_cafe_1341_5737_598f_a7e1);
+    assert_ne!(glider.digest(), Pattern::new(&GLIDER[1..]).digest());
+
+    // every phase of a glider moving in any direction has one of two canonical forms
+    let mut canonical = Vec::new();
+    for &transform in &Transform::ALL {
+        let mut universe = universe_with::<Position>(Rule::life(), &[]);
+        for &offset in &GLIDER {
+            universe.set_cell(
+                Position::ORIGIN + transform.apply(offset.into()),
+                Cell::ALIVE,
+            );
+        }
+        for _ in 0..4 {
+            let pattern = universe.to_pattern();
+            assert_eq!(pattern.canonical_digest(), pattern.canonical().digest());
+            canonical.push(pattern.canonical_digest());
+            universe.evolve();
+        }
+    }
+    canonical.sort();
+    canonical.dedup();
+    assert_eq!(canonical.len(), 2);
+}
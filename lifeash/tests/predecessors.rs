@@ -0,0 +1,74 @@
+use lifeash::{BoundedGrid, Position, PredecessorSearch, Rule, Topology, Transform, Universe};
+use proptest::prelude::*;
+
+mod common;
+
+use common::{evolves_into, universe_with};
+
+#[test]
+fn finds_parents_of_still_lifes_and_oscillators() {
+    let (min, max) = (Position::new(-1, -1), Position::new(3, 3));
+    let block = universe_with(Rule::life(), &[(0, 0), (1, 0), (0, 1), (1, 1)]);
+    let parents = PredecessorSearch::new(&block, min, max).find_all(20);
+    assert_eq!(parents.len(), 20);
+    assert!(parents
+        .iter()
+        .all(|parent| evolves_into(parent, &block, min, max, 1)));
+
+    // three cells of the block are the smallest parent
+    let small = PredecessorSearch::new(&block, min, max).with_max_population(3);
+    assert_eq!(small.find().unwrap().cells().len(), 3);
+    assert_eq!(small.with_max_population(2).find(), None);
+
+    let symmetric = PredecessorSearch::new(&block, min, max)
+        .with_region((-1, -1), (2, 2))
+        .with_symmetry(Transform::Rotate90)
+        .find_all(10);
+    assert!(!symmetric.is_empty());
+    for parent in &symmetric {
+        assert!(parent.symmetry().contains(Transform::Rotate90));
+        assert!(evolves_into(parent, &block, min, max, 1));
+    }
+
+    let blinker = universe_with(Rule::life(), &[(0, -1), (0, 0), (0, 1)]);
+    let (min, max) = (Position::new(-2, -2), Position::new(2, 2));
+    let parent = PredecessorSearch::new(&blinker, min, max)
+        .with_generations(2)
+        .find()
+        .unwrap();
+    assert!(evolves_into(&parent, &blinker, min, max, 2));
+}
+
+#[test]
+fn finds_gardens_of_eden() {
+    // nothing is born or survives, so an alive cell can't have a parent
+    let lonely = universe_with(Rule::new(&[], &[], 2), &[(0, 0)]);
+    assert!(lonely.is_garden_of_eden((-1, -1), (1, 1)));
+    assert!(!lonely.is_garden_of_eden((2, 2), (4, 4)));
+
+    let glider = universe_with(Rule::life(), &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    assert!(!glider.is_garden_of_eden((-1, -1), (3, 3)));
+}
+
+#[test]
+#[should_panic(expected = "infinite plane")]
+fn rejects_bounded_grids() {
+    let torus = Universe::with_grid(Rule::life(), BoundedGrid::new(Topology::Torus, 8, 8));
+    torus.is_garden_of_eden((0, 0), (2, 2));
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn parents_evolve_into_the_target(
+        cells in prop::collection::vec((0i64..3, 0i64..3), 0..9),
+    ) {
+        // the soup itself is a parent, so there always is one
+        let mut target = universe_with(Rule::life(), &cells);
+        target.evolve();
+        let (min, max) = (Position::new(-1, -1), Position::new(3, 3));
+        let parent = PredecessorSearch::new(&target, min, max).find().unwrap();
+        prop_assert!(evolves_into(&parent, &target, min, max, 1));
+    }
+}
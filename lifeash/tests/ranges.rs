@@ -0,0 +1,62 @@
+use lifeash::{Rule, RuleFamily};
+use proptest::prelude::*;
+
+mod common;
+
+use common::{evolved, universe_with, GLIDER};
+
+#[test]
+fn glider_works_in_a_range_of_rules() {
+    let glider = universe_with(Rule::life(), &GLIDER);
+    let range = glider.rule_range(4, RuleFamily::LifeLike).unwrap();
+    assert!(range.contains(&Rule::life()));
+    assert!(range.contains(&"B36/S23".parse().unwrap()));
+    assert!(!range.contains(&"B3/S2".parse().unwrap()));
+    assert!(!range.contains(&"B2-a/S23".parse().unwrap()));
+    assert_eq!(range.to_string(), format!("{} - {}", range.min, range.max));
+
+    // isotropic rules tell more neighborhoods apart, so more of them are free
+    let isotropic = glider.rule_range(4, RuleFamily::Isotropic).unwrap();
+    assert!(isotropic.contains(&range.min) && isotropic.contains(&range.max));
+    assert_ne!(isotropic, range);
+}
+
+#[test]
+fn no_life_like_rule_tells_arrangements_apart() {
+    // in B2e/S the two dominoes evolve differently with the same neighbor counts
+    let rule: Rule = "B2e/S".parse().unwrap();
+    let universe = universe_with(rule.clone(), &[(0, 0), (1, 0), (10, 0), (11, 1)]);
+    assert_eq!(universe.rule_range(1, RuleFamily::LifeLike), None);
+    let range = universe.rule_range(1, RuleFamily::Isotropic).unwrap();
+    assert!(range.contains(&rule));
+}
+
+#[test]
+fn range_ignores_results_memoized_before() {
+    let glider = universe_with(Rule::life(), &GLIDER);
+    let expected = glider.rule_range(4, RuleFamily::Isotropic);
+    // a copy evolving in the same store leaves the results of the glider memoized there
+    let mut copy = glider.clone();
+    for _ in 0..8 {
+        copy.evolve();
+    }
+    assert_eq!(glider.rule_range(4, RuleFamily::Isotropic), expected);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn range_bounds_evolve_alike(
+        cells in prop::collection::vec((-4i64..4, -4i64..4), 1..20),
+        generations in 1usize..6,
+        isotropic in any::<bool>(),
+    ) {
+        let family = if isotropic { RuleFamily::Isotropic } else { RuleFamily::LifeLike };
+        let range = universe_with(Rule::life(), &cells).rule_range(generations, family).unwrap();
+        prop_assert!(range.contains(&Rule::life()));
+        let expected = evolved(Rule::life(), &cells, generations);
+        prop_assert_eq!(evolved(range.min.clone(), &cells, generations), expected.clone());
+        prop_assert_eq!(evolved(range.max, &cells, generations), expected);
+    }
+}
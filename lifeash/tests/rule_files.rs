@@ -0,0 +1,229 @@
+use lifeash::{Cell, DenseGrid, Engine, Icons, Position, Rule, RuleFile, Universe};
+use proptest::prelude::*;
+
+const WIREWORLD: &str = "
+@RULE WireWorld
+
+Electrons move along wires of conductors.
+
+@TABLE
+n_states:4
+neighborhood:Moore
+symmetries:permute
+
+var a={0,1,2,3}
+var b={a}
+var c={a}
+var d={a}
+var e={a}
+var f={a}
+var g={a}
+var h={a}
+# anything but an electron head
+var o={0,2,3}
+var p={o}
+var q={o}
+var r={o}
+var s={o}
+var t={o}
+var u={o}
+
+# heads become tails, tails become conductors
+1,a,b,c,d,e,f,g,h,2
+2,a,b,c,d,e,f,g,h,3
+# conductors become heads next to one or two heads
+3,1,o,p,q,r,s,t,u,1
+3,1,1,o,p,q,r,s,t,1
+
+@COLORS
+1 0 128 255
+2 255 255 255
+3 255 128 0
+
+@ICONS
+XPM
+/* width height num_colors chars_per_pixel */
+\"2 6 2 1\"
+/* colors */
+\". c #000000\"
+\"A c #FFFFFF\"
+/* icon for state 1 */
+\"AA\"
+\"A.\"
+/* icon for state 2 */
+\".A\"
+\"AA\"
+/* icon for state 3 */
+\"..\"
+\"..\"
+";
+
+const TABLE_LIFE: &str = "
+@RULE TableLife
+@TABLE
+n_states:2
+neighborhood:Moore
+symmetries:permute
+var a={0,1}
+var b={a}
+var c={a}
+var d={a}
+var e={a}
+var f={a}
+var g={a}
+var h={a}
+0,1,1,1,0,0,0,0,0,1
+1,1,1,0,0,0,0,0,0,1
+1,1,1,1,0,0,0,0,0,1
+1,a,b,c,d,e,f,g,h,0
+";
+
+const TREE_LIFE: &str = "
+@RULE TreeLife
+@TREE
+num_states=2
+num_neighbors=8
+num_nodes=32
+1 0 0
+2 0 0
+1 0 1
+2 0 2
+3 1 3
+1 1 1
+2 2 5
+3 3 6
+4 4 7
+2 5 0
+3 6 9
+4 7 10
+5 8 11
+3 9 1
+4 10 13
+5 11 14
+6 12 15
+3 1 1
+4 13 17
+5 14 18
+6 15 19
+7 16 20
+4 17 17
+5 18 22
+6 19 23
+7 20 24
+8 21 25
+5 22 22
+6 23 27
+7 24 28
+8 25 29
+9 26 30
+";
+
+fn assert_same_as_life(rule: &Rule) {
+    let life = Rule::life();
+    for neighborhood in 0..=255 {
+        assert_eq!(rule.is_born(neighborhood), life.is_born(neighborhood));
+        assert_eq!(rule.survives(neighborhood), life.survives(neighborhood));
+    }
+}
+
+#[test]
+fn table_runs_life() {
+    let file: RuleFile = TABLE_LIFE.parse().unwrap();
+    assert_eq!(file.rule().to_string(), "TableLife");
+    assert_same_as_life(file.rule());
+}
+
+#[test]
+fn tree_runs_life() {
+    let file: RuleFile = TREE_LIFE.parse().unwrap();
+    assert_eq!(file.rule().states(), 2);
+    assert_same_as_life(file.rule());
+}
+
+#[test]
+fn electron_moves_along_wire() {
+    let file: RuleFile = WIREWORLD.parse().unwrap();
+    let mut universe = Universe::with_rule(file.rule().clone());
+    universe.initialize();
+    for x in 0..10 {
+        universe.set_cell((x, 0), Cell::new(3));
+    }
+    universe.set_cell((0, 0), Cell::new(2));
+    universe.set_cell((1, 0), Cell::new(1));
+
+    for _ in 0..4 {
+        universe.evolve();
+    }
+    assert_eq!(universe.get_cell((5, 0)), Cell::new(1));
+    assert_eq!(universe.get_cell((4, 0)), Cell::new(2));
+    assert_eq!(universe.census(), vec![0, 1, 1, 8]);
+}
+
+#[test]
+fn parses_metadata() {
+    let file: RuleFile = WIREWORLD.parse().unwrap();
+    assert_eq!(file.colors()[0], (Cell::new(1), [0, 128, 255]));
+    assert_eq!(file.colors().len(), 3);
+
+    let black = Some([0, 0, 0]);
+    let white = Some([255, 255, 255]);
+    match file.icons() {
+        [Icons::Images { size: 2, icons }] => {
+            assert_eq!(icons.len(), 3);
+            assert_eq!(icons[0], vec![white, white, white, black]);
+        }
+        icons => panic!("unexpected icons {:?}", icons),
+    }
+}
+
+#[test]
+fn rejects_invalid_files() {
+    let table = "@RULE Broken\n@TABLE\nn_states:2\nneighborhood:Moore\nsymmetries:rotate4\n";
+    for file in &[
+        "@TABLE\nn_states:2\n",
+        "@RULE Empty\n",
+        &format!("{}{}", table, "0,1,1,1,0,0,0,0,0\n"),
+        &format!("{}{}", table, "0,1,1,1,0,0,0,0,0,2\n"),
+        &format!("{}{}", table, "0,1,1,1,0,0,0,0,0,x\n"),
+        "@RULE Broken\n@TABLE\nn_states:2\nneighborhood:Moore\nsymmetries:rotate6\n",
+        "@RULE Broken\n@TREE\nnum_states=2\nnum_neighbors=4\nnum_nodes=1\n1 0 1\n",
+        // empty space comes to life
+        &format!("{}{}", table, "0,0,0,0,0,0,0,0,0,1\n"),
+        "@RULE Broken\n@TREE\nnum_states=2\nnum_neighbors=4\nnum_nodes=5\n1 1 1\n2 0 0\n3 1 1\n4 2 2\n5 3 3\n",
+    ] {
+        assert!(file.parse::<RuleFile>().is_err(), "{} was accepted", file);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn wireworld_matches_reference(
+        cells in prop::collection::vec((-16i64..16, -16i64..16, 1u8..4), 0..128),
+        steps in 0usize..24,
+    ) {
+        let rule = WIREWORLD.parse::<RuleFile>().unwrap().rule().clone();
+        let mut hashlife = Universe::with_rule(rule.clone());
+        hashlife.initialize();
+        let mut reference = DenseGrid::with_rule(rule);
+        for &(x, y, state) in &cells {
+            hashlife.set_cell((x, y), Cell::new(state));
+            reference.set_cell(Position::new(x, y), Cell::new(state));
+        }
+
+        for _ in 0..steps {
+            hashlife.evolve();
+            reference.evolve();
+        }
+
+        // wires never grow, so nothing leaves the initial box
+        for y in -17..17 {
+            for x in -17..17 {
+                let pos = Position::new(x, y);
+                prop_assert_eq!(hashlife.get_cell(pos), reference.get_cell(pos));
+            }
+        }
+        prop_assert_eq!(hashlife.population(), reference.population());
+    }
+}
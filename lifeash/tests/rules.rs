@@ -0,0 +1,293 @@
+use lifeash::{Cell, Neighborhood, Offset, Rule, Universe};
+
+#[test]
+fn parses_life_like_rules() {
+    let life: Rule = "B3/S23".parse().unwrap();
+    assert_eq!(life, Rule::life());
+    assert_eq!("23/3".parse::<Rule>().unwrap(), life);
+    assert_eq!("b3/s23".parse::<Rule>().unwrap(), life);
+    assert_eq!(life.to_string(), "B3/S23");
+
+    let highlife: Rule = "B36/S23".parse().unwrap();
+    assert!(highlife.is_born(0b1110_0111));
+    assert!(!highlife.survives(0b1110_0111));
+}
+
+#[test]
+fn parses_generations_rules() {
+    let brians_brain: Rule = "B2/S/C3".parse().unwrap();
+    assert_eq!(brians_brain.states(), 3);
+    assert_eq!("/2/3".parse::<Rule>().unwrap(), brians_brain);
+    assert_eq!(brians_brain.to_string(), "B2/S/C3");
+
+    let star_wars: Rule = "345/2/4".parse().unwrap();
+    assert_eq!(star_wars.to_string(), "B2/S345/C4");
+}
+
+#[test]
+fn parses_non_totalistic_rules() {
+    let rule: Rule = "B2-a/S12".parse().unwrap();
+    // two neighbors next to each other at the north east corner don't cause a birth
+    assert!(!rule.is_born(0b0110_0000));
+    assert!(!rule.is_born(0b0000_0011));
+    // but two opposite ones do
+    assert!(rule.is_born(0b0100_0010));
+    assert_eq!(rule.to_string(), "B2-a/S12");
+
+    let tlife: Rule = "b3/s2-I34Q".parse().unwrap();
+    assert!(!tlife.survives(0b0100_0010));
+    assert!(tlife.survives(0b0100_1000));
+    assert_eq!(tlife.to_string(), "B3/S2-i34q");
+
+    // listing every letter is the same as listing none
+    assert_eq!("B3/S2cekain3".parse::<Rule>().unwrap(), Rule::life());
+    assert_eq!(
+        "B3-cekainyqjr3/S23".parse::<Rule>().unwrap().to_string(),
+        "B3/S23"
+    );
+    assert_eq!(
+        "B4-acekijnqrtwyz/S".parse::<Rule>().unwrap().to_string(),
+        "B/S"
+    );
+}
+
+#[test]
+fn parses_von_neumann_and_hexagonal_rules() {
+    let rule: Rule = "B1/S2V".parse().unwrap();
+    assert_eq!(rule.neighborhood(), Neighborhood::VonNeumann);
+    assert_eq!(
+        rule,
+        Rule::with_neighborhood(Neighborhood::VonNeumann, &[1], &[2], 2)
+    );
+    assert_eq!(rule.to_string(), "B1/S2V");
+    // the corners don't count
+    assert!(rule.is_born(0b0100_0000));
+    assert!(rule.is_born(0b1110_0101));
+    assert!(!rule.is_born(0b1010_0101));
+    assert_eq!("2/1v".parse::<Rule>().unwrap(), rule);
+
+    let hex: Rule = "B2/S34H".parse().unwrap();
+    assert_eq!(hex.neighborhood(), Neighborhood::Hexagonal);
+    assert_eq!(hex.to_string(), "B2/S34H");
+    // the north east and south west neighbors are ignored
+    assert!(hex.is_born(0b1100_0000));
+    assert!(hex.is_born(0b1110_0100));
+    assert!(!hex.is_born(0b0010_0100));
+    assert_eq!("B2/S/C3H".parse::<Rule>().unwrap().states(), 3);
+}
+
+#[test]
+fn parses_hexagonal_non_totalistic_rules() {
+    let rule: Rule = "B2o/S2m34pH".parse().unwrap();
+    assert_eq!(rule.to_string(), "B2o/S2m34pH");
+    // neighbors next to each other on the hexagon, north and east
+    assert!(rule.is_born(0b0100_1000));
+    // north west and east are one apart
+    assert!(!rule.is_born(0b1000_1000));
+    assert!(rule.survives(0b1000_1000));
+    // four neighbors with the two dead ones, north and south, opposite each other
+    assert!(rule.survives(0b1001_1001));
+    assert!(!rule.survives(0b1100_1001));
+
+    // the letters split the arrangements without leaving any out
+    assert_eq!(
+        "B2omp3pmo4mop/SH".parse::<Rule>().unwrap().to_string(),
+        "B234/SH"
+    );
+    assert_eq!("B3mp/SH".parse::<Rule>().unwrap().to_string(), "B3-o/SH");
+    assert_eq!("B4-om/SH".parse::<Rule>().unwrap().to_string(), "B4p/SH");
+}
+
+// the neighbors of a cell with the given offsets alive
+fn alive_at(alive: &[(i64, i64)]) -> impl Fn(Offset) -> Cell + '_ {
+    move |offset| {
+        if alive.contains(&(offset.dx, offset.dy)) {
+            Cell::ALIVE
+        } else {
+            Cell::DEAD
+        }
+    }
+}
+
+#[test]
+fn parses_larger_than_life_rules() {
+    let bosco: Rule = "R5,C0,M1,S34..58,B34..45,NM".parse().unwrap();
+    assert_eq!(bosco.radius(), 5);
+    assert_eq!(bosco.states(), 2);
+    assert_eq!(bosco.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+    assert_eq!(
+        "r5,c2,m1,s34..58,b34..45,nm".parse::<Rule>().unwrap(),
+        bosco
+    );
+
+    let rule: Rule = "R2,C4,M0,S1..2,B2..3,NN".parse().unwrap();
+    assert_eq!(rule.neighborhood(), Neighborhood::VonNeumann);
+    assert_eq!(rule.states(), 4);
+    assert_eq!(rule.to_string(), "R2,C4,M0,S1..2,B2..3,NN");
+    // two cells away in a straight line count, but not diagonally
+    assert_eq!(
+        rule.next_around(Cell::DEAD, alive_at(&[(0, -2), (2, 0)])),
+        Cell::ALIVE
+    );
+    assert_eq!(
+        rule.next_around(Cell::DEAD, alive_at(&[(0, -2), (2, 2)])),
+        Cell::DEAD
+    );
+    assert_eq!(
+        rule.next_around(Cell::ALIVE, alive_at(&[(2, 2), (0, 3)])),
+        Cell::new(2)
+    );
+    assert_eq!(rule.next_around(Cell::new(3), alive_at(&[])), Cell::DEAD);
+
+    // with M1 an alive cell counts itself, so this is Life
+    let rule: Rule = "R1,C0,M1,S3..4,B3..3,NM".parse().unwrap();
+    for neighborhood in 0..=255 {
+        assert_eq!(
+            rule.is_born(neighborhood),
+            Rule::life().is_born(neighborhood)
+        );
+        assert_eq!(
+            rule.survives(neighborhood),
+            Rule::life().survives(neighborhood)
+        );
+    }
+}
+
+#[test]
+fn parses_margolus_rules() {
+    let billiards = "MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15";
+    let rule: Rule = billiards.parse().unwrap();
+    assert_eq!(
+        rule,
+        Rule::margolus([0, 8, 4, 3, 2, 5, 9, 7, 1, 6, 10, 11, 12, 13, 14, 15])
+    );
+    assert_eq!(rule.to_string(), billiards);
+    assert_eq!(rule.states(), 2);
+    assert_eq!(rule.background(3), Cell::DEAD);
+    assert_eq!(
+        "ms,d0; 8;4;3;2;5;9;7;1;6;10;11;12;13;14;15"
+            .parse::<Rule>()
+            .unwrap(),
+        rule
+    );
+
+    // Critters turns the empty block into the full one and back
+    let critters: Rule = "MS,D15;14;13;3;11;5;6;1;7;9;10;2;12;4;8;0".parse().unwrap();
+    let backgrounds: Vec<_> = (0..4).map(|gen| critters.background(gen)).collect();
+    assert_eq!(
+        backgrounds,
+        [Cell::DEAD, Cell::ALIVE, Cell::DEAD, Cell::ALIVE]
+    );
+}
+
+#[test]
+fn billiard_ball_alternates_partitions() {
+    let rule: Rule = "MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15".parse().unwrap();
+    let mut universe = Universe::with_rule(rule);
+    universe.initialize();
+    universe.set_cell((0, 0), Cell::ALIVE);
+    // a ball moves into the opposite corner of its block, which is the corner of the
+    // next block in the other partition
+    for generation in 1..=20 {
+        universe.evolve();
+        assert_eq!(
+            universe.alive_cells(),
+            vec![(generation, generation).into()]
+        );
+    }
+
+    // a universe sharing the store starts in the even partition, although the store has
+    // just evolved an odd generation
+    let mut other = Universe::with_store(universe.store());
+    other.initialize();
+    other.set_cell((1, 1), Cell::ALIVE);
+    other.evolve();
+    assert_eq!(other.alive_cells(), vec![(0, 0).into()]);
+}
+
+#[test]
+fn rejects_invalid_rules() {
+    for rule in &[
+        "",
+        "B3",
+        "B9/S23",
+        "B3/S23/C1",
+        "B3/S2x",
+        "B3/23",
+        "B1a/S",
+        "B3-/S",
+        "B0c/S",
+        "B5/SV",
+        "B7/SH",
+        "B1a/SV",
+        "B2a/SH",
+        "B1o/SH",
+        "R5,C0,M1,S34..58,B34..45",
+        "R0,C0,M1,S34..58,B34..45,NM",
+        "R501,C0,M1,S34..58,B34..45,NM",
+        "R5,C0,M2,S34..58,B34..45,NM",
+        "R5,C0,M1,S34,B34..45,NM",
+        "R5,C0,M1,S34..58,B0..45,NM",
+        "R5,C0,M1,S34..58,B34..45,NX",
+        "MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14",
+        "MS,D0;8;4;3;2;5;9;7;1;6;10;11;12;13;14;16",
+        "MS,D1;8;4;3;2;5;9;7;1;6;10;11;12;13;14;15",
+        "MS,D15;8;4;3;2;5;9;7;1;6;10;11;12;13;14;7",
+    ] {
+        assert!(rule.parse::<Rule>().is_err(), "{} was accepted", rule);
+    }
+}
+
+#[test]
+fn decays_through_refractory_states() {
+    let star_wars: Rule = "345/2/4".parse().unwrap();
+    let mut two = [Cell::DEAD; 8];
+    two[..2].copy_from_slice(&[Cell::ALIVE; 2]);
+    let mut three = [Cell::new(2); 8];
+    three[..3].copy_from_slice(&[Cell::ALIVE; 3]);
+    assert_eq!(star_wars.next(Cell::DEAD, &two), Cell::ALIVE);
+    assert_eq!(star_wars.next(Cell::ALIVE, &three), Cell::ALIVE);
+    assert_eq!(star_wars.next(Cell::ALIVE, &two), Cell::new(2));
+    // refractory cells ignore their neighbors
+    assert_eq!(star_wars.next(Cell::new(2), &three), Cell::new(3));
+    assert_eq!(star_wars.next(Cell::new(3), &two), Cell::DEAD);
+}
+
+#[test]
+fn b0_turns_background_alive() {
+    let flashing: Rule = "B03/S23".parse().unwrap();
+    let backgrounds: Vec<_> = (0..4).map(|gen| flashing.background(gen)).collect();
+    assert_eq!(
+        backgrounds,
+        [Cell::DEAD, Cell::ALIVE, Cell::DEAD, Cell::ALIVE]
+    );
+
+    let inverse_life: Rule = "B0123478/S34678".parse().unwrap();
+    let backgrounds: Vec<_> = (0..4).map(|gen| inverse_life.background(gen)).collect();
+    assert_eq!(
+        backgrounds,
+        [Cell::DEAD, Cell::ALIVE, Cell::ALIVE, Cell::ALIVE]
+    );
+
+    assert_eq!(Rule::life().background(1), Cell::DEAD);
+}
+
+#[test]
+fn inverse_life_runs_life_on_the_complement() {
+    // a block of dead cells in front of the alive background is still life in inverse life
+    let mut universe = Universe::with_rule("B0123478/S34678".parse().unwrap());
+    universe.initialize();
+    universe.evolve();
+    for &pos in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+        universe.set_cell(pos, Cell::DEAD);
+    }
+
+    for _ in 0..3 {
+        universe.evolve();
+    }
+    assert_eq!(universe.background(), Cell::ALIVE);
+    assert_eq!(universe.population(), 4);
+    assert_eq!(universe.census(), vec![4, 0]);
+    assert_eq!(universe.get_cell((1, 1)), Cell::DEAD);
+    assert_eq!(universe.get_cell((2, 2)), Cell::ALIVE);
+}
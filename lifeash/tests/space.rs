@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+
+use lifeash::{Cell, Position, Position3, SpaceRule, SpaceUniverse};
+use proptest::prelude::*;
+
+// evolves the set of alive cells directly, counting the neighbors of every cell next to one
+fn naive(rule: SpaceRule, cells: &HashSet<Position3>) -> HashSet<Position3> {
+    let around = |pos: Position3| {
+        (-1..=1).flat_map(move |dz| {
+            (-1..=1).flat_map(move |dy| {
+                (-1..=1).map(move |dx| Position3::new(pos.x + dx, pos.y + dy, pos.z + dz))
+            })
+        })
+    };
+    let candidates: HashSet<Position3> = cells.iter().flat_map(|&pos| around(pos)).collect();
+    candidates
+        .into_iter()
+        .filter(|&pos| {
+            let count = around(pos)
+                .filter(|&neighbor| neighbor != pos && cells.contains(&neighbor))
+                .count() as u32;
+            if cells.contains(&pos) {
+                rule.survives(count)
+            } else {
+                rule.is_born(count)
+            }
+        })
+        .collect()
+}
+
+fn space_rule() -> impl Strategy<Value = SpaceRule> {
+    (
+        prop::collection::vec(1u8..=26, 0..4),
+        prop::collection::vec(0u8..=26, 0..4),
+    )
+        .prop_map(|(birth, survival)| SpaceRule::new(&birth, &survival))
+}
+
+#[test]
+fn parses_rules() {
+    for &(rule, expected) in &[
+        ("B5/S45", "B5/S45"),
+        ("B45/S5", "B45/S5"),
+        ("B5,6/S4,5,10", "B5,6/S4,5,10"),
+        ("B13,14/S", "B13,14/S"),
+        ("B4/S", "B4/S"),
+    ] {
+        assert_eq!(rule.parse::<SpaceRule>().unwrap().to_string(), expected);
+    }
+    assert_eq!(SpaceRule::default(), "B5/S45".parse().unwrap());
+
+    for rule in &[
+        "", "B5", "S45/B5", "B0/S45", "B5/S4,27", "B5,/S4", "B5/S4/S3",
+    ] {
+        assert!(rule.parse::<SpaceRule>().is_err(), "{} was accepted", rule);
+    }
+}
+
+#[test]
+fn slices_planes_for_viewing() {
+    let mut space = SpaceUniverse::new(SpaceRule::default());
+    for &(x, y, z) in &[(0, 0, 0), (1, 0, 0), (-20, 7, 3), (5, -5, 3), (2, 2, -40)] {
+        space.set_cell((x, y, z), Cell::ALIVE);
+    }
+    assert_eq!(space.population(), 5);
+
+    let plane = space.slice(3);
+    assert_eq!(plane.population(), 2);
+    assert_eq!(plane.get_cell(Position::new(-20, 7)), Cell::ALIVE);
+    assert_eq!(plane.get_cell(Position::new(5, -5)), Cell::ALIVE);
+    assert_eq!(space.slice(0).population(), 2);
+    assert_eq!(space.slice(-40).population(), 1);
+    assert_eq!(space.slice(1).population(), 0);
+    assert_eq!(space.slice(1000).population(), 0);
+}
+
+#[test]
+fn square_jumps_into_neighboring_planes() {
+    // four cells in a square each have three neighbors, so under B5/S45 nothing survives
+    let mut space = SpaceUniverse::new("B5/S45".parse().unwrap());
+    for &(x, y) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+        space.set_cell((x, y, 0), Cell::ALIVE);
+    }
+    space.evolve();
+    assert_eq!(space.population(), 0);
+    assert_eq!(space.generation(), 1);
+
+    let mut space = SpaceUniverse::new(SpaceRule::new(&[4], &[]));
+    for &(x, y) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+        space.set_cell((x, y, 0), Cell::ALIVE);
+    }
+    space.evolve();
+    // the planes above and below see all four cells, the square itself dies
+    let mut cells = space.alive_cells();
+    cells.sort();
+    let mut expected: Vec<Position3> = [-1, 1]
+        .iter()
+        .flat_map(|&z| {
+            [(0, 0), (0, 1), (1, 0), (1, 1)]
+                .iter()
+                .map(move |&(x, y)| Position3::new(x, y, z))
+        })
+        .collect();
+    expected.sort();
+    assert_eq!(cells, expected);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(48))]
+
+    #[test]
+    fn space_matches_naive(
+        rule in space_rule(),
+        pattern in prop::collection::vec((-6i64..6, -6i64..6, -6i64..6), 0..40),
+        steps in 0usize..8,
+    ) {
+        let mut space = SpaceUniverse::new(rule);
+        let mut cells = HashSet::new();
+        for &pos in &pattern {
+            space.set_cell(pos, Cell::ALIVE);
+            cells.insert(Position3::from(pos));
+        }
+        for _ in 0..steps {
+            space.evolve();
+            cells = naive(rule, &cells);
+        }
+
+        let mut alive = space.alive_cells();
+        alive.sort();
+        let mut expected: Vec<Position3> = cells.into_iter().collect();
+        expected.sort();
+        prop_assert_eq!(space.population() as usize, expected.len());
+        for &pos in &expected {
+            prop_assert_eq!(space.get_cell(pos), Cell::ALIVE);
+        }
+        prop_assert_eq!(alive, expected);
+    }
+}
@@ -0,0 +1,69 @@
+use lifeash::{Cell, Position, Rule, SharedStore, Universe};
+
+const R_PENTOMINO: [(i64, i64); 5] = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+
+fn universe_with(store: &SharedStore, cells: &[(i64, i64)]) -> Universe {
+    let mut universe = Universe::with_store(store);
+    universe.initialize();
+    for &pos in cells {
+        universe.set_cell(pos, Cell::ALIVE);
+    }
+    universe
+}
+
+fn sorted_cells(universe: &Universe) -> Vec<Position> {
+    let mut cells = universe.alive_cells();
+    cells.sort();
+    cells
+}
+
+#[test]
+fn shared_stores_reuse_results() {
+    let store = SharedStore::with_rule(Rule::life());
+    let mut first = universe_with(&store, &R_PENTOMINO);
+    for _ in 0..200 {
+        first.evolve();
+    }
+    let len = store.lock().len();
+
+    // the same pattern only meets interned nodes with memoized results
+    let mut second = universe_with(&store, &R_PENTOMINO);
+    assert!(second.store().ptr_eq(&store));
+    for _ in 0..200 {
+        second.evolve();
+    }
+    assert_eq!(store.lock().len(), len);
+    assert_eq!(sorted_cells(&second), sorted_cells(&first));
+}
+
+#[test]
+fn shared_stores_stay_correct() {
+    let store = SharedStore::with_rule(Rule::life());
+    let patterns: [&[(i64, i64)]; 3] = [
+        &R_PENTOMINO,
+        &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)],
+        &[(0, 0), (1, 0), (2, 0), (7, 7)],
+    ];
+    let mut shared: Vec<Universe> = patterns
+        .iter()
+        .map(|cells| universe_with(&store, cells))
+        .collect();
+    let mut private: Vec<Universe> = patterns
+        .iter()
+        .map(|cells| universe_with(&SharedStore::with_rule(Rule::life()), cells))
+        .collect();
+
+    // the universes take turns, each one with different generations
+    for step in 0..120 {
+        for (i, (shared, private)) in shared.iter_mut().zip(&mut private).enumerate() {
+            if step % (i + 1) == 0 {
+                shared.evolve();
+                private.evolve();
+            }
+        }
+    }
+    for (shared, private) in shared.iter().zip(&private) {
+        assert_eq!(shared.generation(), private.generation());
+        assert_eq!(sorted_cells(shared), sorted_cells(private));
+    }
+}
@@ -0,0 +1,76 @@
+use lifeash::{Cell, Pattern, Position, Rule, Transform};
+
+mod common;
+
+use common::{universe_with, GLIDER};
+
+#[test]
+fn classifies_common_objects() {
+    let cases: &[(&str, &[(i64, i64)])] = &[
+        ("D8_4", &[(0, 0), (1, 0), (0, 1), (1, 1)]),
+        ("D8_1", &[(1, 0), (0, 1), (2, 1), (1, 2)]),
+        ("D4_+1", &[(0, 0), (1, 0), (2, 0)]),
+        ("D4_+2", &[(1, 0), (2, 0), (0, 1), (3, 1), (1, 2), (2, 2)]),
+        ("D4_+4", &[(0, 0), (3, 0), (0, 1), (3, 1)]),
+        ("D4_x1", &[(0, 0), (1, 1), (2, 2)]),
+        (
+            "C4_1",
+            &[
+                (1, 0),
+                (2, 1),
+                (0, 1),
+                (-1, 2),
+                (-1, 0),
+                (-2, -1),
+                (0, -1),
+                (1, -2),
+            ],
+        ),
+        ("C2_2", &[(0, 0), (1, 0), (1, 1), (2, 1)]),
+        ("C2_1", &[(0, 0), (1, 0), (1, 1), (1, 2), (2, 2)]),
+        ("D2_x", &[(0, 0), (1, 0), (0, 1)]),
+        ("C1", &GLIDER),
+        ("D2_+1", &[(1, 0), (0, 1), (1, 1), (2, 1)]),
+        ("C1", &[(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)]),
+    ];
+    for &(class, cells) in cases {
+        let symmetry = Pattern::new(cells).symmetry();
+        assert_eq!(symmetry.class(), class, "{:?}", cells);
+        assert_eq!(symmetry.transforms[0], Transform::Identity);
+    }
+
+    let beehive = universe_with(
+        Rule::life(),
+        &[(11, 5), (12, 5), (10, 6), (13, 6), (11, 7), (12, 7)],
+    );
+    let symmetry = beehive.symmetry();
+    assert_eq!(symmetry.double_center, Position::new(23, 12));
+    assert_eq!(symmetry.to_string(), "D4_+2 around (23, 12)/2");
+}
+
+#[test]
+fn finds_glide_and_rotation_between_phases() {
+    let glider = universe_with(Rule::life(), &GLIDER);
+    let glide = glider.phase_symmetry(4).unwrap();
+    assert_eq!(glide.generations, 2);
+    assert!(glide.is_reflection());
+    let mut later = glider.clone();
+    later.evolve();
+    later.evolve();
+    for &(x, y) in &GLIDER {
+        let pos = Position::ORIGIN + glide.transform.apply((x, y).into()) + glide.offset;
+        assert_eq!(later.get_cell(pos), Cell::ALIVE);
+    }
+    assert_eq!(later.population(), 5);
+
+    let blinker = universe_with(Rule::life(), &[(0, 0), (1, 0), (2, 0)])
+        .phase_symmetry(2)
+        .unwrap();
+    assert_eq!(blinker.generations, 1);
+    assert_eq!(blinker.transform, Transform::Rotate90);
+    assert!(!blinker.is_reflection());
+
+    // the block repeats before anything else
+    let block = universe_with(Rule::life(), &[(0, 0), (1, 0), (0, 1), (1, 1)]);
+    assert_eq!(block.phase_symmetry(5), None);
+}
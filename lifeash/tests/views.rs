@@ -0,0 +1,71 @@
+use std::{sync::mpsc, thread, time::Duration};
+
+use lifeash::{Cell, Position, Rule, SharedStore, Universe, UniverseView};
+
+const R_PENTOMINO: [(i64, i64); 5] = [(1, 0), (2, 0), (0, 1), (1, 1), (1, 2)];
+
+fn r_pentomino(store: &SharedStore) -> Universe {
+    let mut universe = Universe::with_store(store);
+    universe.initialize();
+    for &pos in &R_PENTOMINO {
+        universe.set_cell(pos, Cell::ALIVE);
+    }
+    universe
+}
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn views_are_read_while_evolving() {
+    assert_send_sync::<UniverseView>();
+    let mut universe = r_pentomino(&SharedStore::with_rule(Rule::life()));
+    for _ in 0..50 {
+        universe.evolve();
+    }
+    let view = universe.view();
+    let (population, cells) = (universe.population(), universe.alive_cells());
+
+    let reader = thread::spawn({
+        let view = view.clone();
+        move || {
+            let mut read = Vec::new();
+            for _ in 0..20 {
+                read = cells
+                    .iter()
+                    .filter(|&&pos| view.get_cell(pos) == Cell::ALIVE)
+                    .copied()
+                    .collect::<Vec<Position>>();
+                assert_eq!(view.population(), population);
+            }
+            read.len()
+        }
+    });
+    for _ in 0..200 {
+        universe.evolve();
+    }
+    assert_eq!(reader.join().unwrap(), population as usize);
+    assert_eq!(view.generation(), 50);
+    assert_eq!(universe.view().generation(), 250);
+}
+
+#[test]
+fn views_dont_lock_the_store() {
+    let store = SharedStore::with_rule(Rule::life());
+    let mut universe = r_pentomino(&store);
+    for _ in 0..10 {
+        universe.evolve();
+    }
+
+    // as if another universe sharing the store was evolving
+    let guard = store.lock();
+    let (sender, receiver) = mpsc::channel();
+    let viewer = thread::spawn(move || {
+        let view = universe.view();
+        sender.send((view.generation(), view.population())).unwrap();
+        universe
+    });
+    let viewed = receiver.recv_timeout(Duration::from_secs(10));
+    drop(guard);
+    let universe = viewer.join().unwrap();
+    assert_eq!(viewed, Ok((10, universe.population())));
+}